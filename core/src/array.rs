@@ -0,0 +1,270 @@
+use crate::element::Element;
+use crate::krate::velcro_private;
+use crate::seq::SeqInput;
+use crate::value::{Value, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::{self, Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Expr, ExprArray, ExprParen, ExprPath, ExprRepeat};
+
+/// The input of `array!`, which creates a fixed-size array whose length is computed at
+/// compile time.
+pub struct ArrayInput(SeqInput<Verbatim>);
+
+impl Parse for ArrayInput {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(ArrayInput(input.parse()?))
+    }
+}
+
+/// An item of an `array!` input, each of which has a length that is known at compile
+/// time.
+enum Item<'a> {
+    /// A single value.
+    One(&'a Expr),
+    /// A spread of an array literal, `..[a, b, c]`.
+    Literal(&'a ExprArray),
+    /// A spread of a repeated array, `..[value; len]`.
+    Repeat(&'a ExprRepeat),
+    /// A spread of an array constant, `..TABLE`.
+    Const(&'a ExprPath),
+}
+
+impl<'a> Item<'a> {
+    fn from_element(element: &'a Element<Value<Verbatim>>) -> parse::Result<Self> {
+        match element {
+            Element::Value(Value::One(value)) => Ok(Item::One(&value.expr)),
+            Element::Value(Value::Many(values)) => match remove_parens(&values.expr) {
+                Expr::Array(array) => Ok(Item::Literal(array)),
+                Expr::Repeat(repeat) => Ok(Item::Repeat(repeat)),
+                Expr::Path(path) if is_const_name(path) => Ok(Item::Const(path)),
+                Expr::Path(path) => Err(syn::Error::new(
+                    path.span(),
+                    "only array constants can be spread in `array!`, because the length \
+                     of the array must be known at compile time; a local variable can't \
+                     be used",
+                )),
+                expr => Err(syn::Error::new(
+                    expr.span(),
+                    "the length of a spread in `array!` must be known at compile time; \
+                     spread an array literal or an array constant instead",
+                )),
+            },
+            _ => Err(syn::Error::new(
+                Span::call_site(),
+                "conditional elements can't be used in `array!`, because the length of \
+                 the array must be known at compile time",
+            )),
+        }
+    }
+
+    /// Returns true if the item can be written directly into an array literal.
+    fn is_literal(&self) -> bool {
+        matches!(self, Item::One(_) | Item::Literal(_))
+    }
+
+    /// Generates a constant expression for the number of values of the item.
+    fn len(&self, len_fn: &TokenStream) -> TokenStream {
+        match self {
+            Item::One(_) => quote!(1),
+            Item::Literal(array) => {
+                let len = array.elems.len();
+                quote!(#len)
+            }
+            Item::Repeat(repeat) => {
+                let len = &repeat.len;
+                quote!((#len))
+            }
+            Item::Const(path) => quote!(#len_fn(&#path)),
+        }
+    }
+}
+
+impl ArrayInput {
+    pub fn into_output(self) -> TokenStream {
+        let items: Result<Vec<_>, _> = self.0.values().map(Item::from_element).collect();
+        let items = match items {
+            Ok(items) => items,
+            Err(error) => return error.to_compile_error(),
+        };
+        if items.iter().all(Item::is_literal) {
+            literal_output(&items)
+        } else {
+            copied_output(&items)
+        }
+    }
+}
+
+/// Generates an array literal, with the values of any spread array literals inlined.
+fn literal_output(items: &[Item<'_>]) -> TokenStream {
+    let values = items.iter().flat_map(|item| match item {
+        Item::One(expr) => vec![*expr],
+        Item::Literal(array) => array.elems.iter().collect(),
+        _ => unreachable!("only single values and array literals can be inlined"),
+    });
+    quote! {
+        [#(#values),*]
+    }
+}
+
+/// Generates a block which fills an array of the computed length by copying each item
+/// into it. This only uses constructs that are allowed in `const` and `static`
+/// initializers, and requires the values to be `Copy`.
+fn copied_output(items: &[Item<'_>]) -> TokenStream {
+    let private = velcro_private();
+    let len_fn = quote!(#private::array_len);
+    let len_ident = Ident::new("LEN", Span::mixed_site());
+    let array = Ident::new("array", Span::mixed_site());
+    let spread = Ident::new("spread", Span::mixed_site());
+    let index = Ident::new("index", Span::mixed_site());
+
+    let lens: Vec<_> = items.iter().map(|item| item.len(&len_fn)).collect();
+    let total_len = quote!(0 #(+ #lens)*);
+
+    // The array is initialized by repeating the first value that isn't from a constant,
+    // which doesn't need to be written again. It is evaluated first, which is only
+    // observable if an earlier item has side effects, and constants have none.
+    let (init, skip) =
+        match items.iter().position(|item| match item {
+            Item::Literal(array) => !array.elems.is_empty(),
+            Item::Const(_) => false,
+            _ => true,
+        }) {
+            Some(position) => {
+                let first = match &items[position] {
+                    Item::One(expr) => quote!(#expr),
+                    Item::Literal(array) => {
+                        let value = &array.elems[0];
+                        quote!(#value)
+                    }
+                    Item::Repeat(repeat) => {
+                        let value = &repeat.expr;
+                        quote!(#value)
+                    }
+                    Item::Const(_) => unreachable!("a constant is not used as the first value"),
+                };
+                (quote!([#first; #len_ident]), Some(position))
+            }
+            // Otherwise, the first value of the first non-empty constant is used, and there
+            // is none if they are all empty
+            None => {
+                let first = items.iter().rev().fold(
+                    quote!(::core::option::Option::None),
+                    |otherwise, item| match item {
+                        Item::Const(path) => quote! {
+                            match #private::array_first(&#path) {
+                                ::core::option::Option::None => #otherwise,
+                                first => first,
+                            }
+                        },
+                        _ => otherwise,
+                    },
+                );
+                (
+                    quote!(#private::array_filled::<_, #len_ident>(#first)),
+                    None,
+                )
+            }
+        };
+
+    let updates = items.iter().enumerate().map(|(position, item)| {
+        let offset = &lens[..position];
+        let offset = quote!(0 #(+ #offset)*);
+        let skip_first = skip == Some(position);
+        match item {
+            Item::One(_) if skip_first => TokenStream::new(),
+            Item::One(expr) => quote_spanned! {
+                expr.span() =>
+                #array[#offset] = #expr;
+            },
+            Item::Literal(literal) => {
+                let updates = literal
+                    .elems
+                    .iter()
+                    .enumerate()
+                    .skip(if skip_first { 1 } else { 0 })
+                    .map(|(i, value)| {
+                        quote! {
+                            #array[#offset + #i] = #value;
+                        }
+                    });
+                quote!(#(#updates)*)
+            }
+            Item::Repeat(_) if skip_first => TokenStream::new(),
+            Item::Repeat(repeat) => quote! {
+                let #spread = #repeat;
+                let mut #index = 0;
+                while #index < #spread.len() {
+                    #array[#offset + #index] = #spread[#index];
+                    #index += 1;
+                }
+            },
+            Item::Const(path) => quote! {
+                let mut #index = 0;
+                while #index < #len_fn(&#path) {
+                    #array[#offset + #index] = #path[#index];
+                    #index += 1;
+                }
+            },
+        }
+    });
+
+    quote! {{
+        const #len_ident: usize = #total_len;
+        let mut #array = #init;
+        #(#updates)*
+        #array
+    }}
+}
+
+/// Returns true if a path names a constant, by the convention that constants are
+/// upper case. Other paths, such as local variables, don't have a constant length.
+fn is_const_name(path: &ExprPath) -> bool {
+    path.path.segments.last().is_some_and(|segment| {
+        let name = segment.ident.to_string();
+        name.chars().any(|c| c.is_ascii_uppercase()) && !name.chars().any(char::is_lowercase)
+    })
+}
+
+fn remove_parens(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(ExprParen { expr, .. }) => remove_parens(expr),
+        expr => expr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_items(input: &str) -> Vec<parse::Result<&'static str>> {
+        let input: ArrayInput = syn::parse2(TokenStream::from_str(input).unwrap()).unwrap();
+        input
+            .0
+            .values()
+            .map(|element| {
+                Item::from_element(element).map(|item| match item {
+                    Item::One(_) => "one",
+                    Item::Literal(_) => "literal",
+                    Item::Repeat(_) => "repeat",
+                    Item::Const(_) => "const",
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spreads_of_known_length() {
+        let items = parse_items("1, ..[2, 3], ..[0; 4], ..(TABLE)");
+        let kinds: Vec<_> = items.into_iter().map(Result::unwrap).collect();
+        assert_eq!(kinds, ["one", "literal", "repeat", "const"]);
+    }
+
+    #[test]
+    fn spreads_of_unknown_length_are_errors() {
+        let items = parse_items("..values.iter(), ..0..4, if x => 1, ..local, ..m::Table");
+        assert!(items.iter().all(Result::is_err));
+    }
+}
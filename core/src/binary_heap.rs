@@ -0,0 +1,102 @@
+use crate::capacity::Capacity;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::alloc_crate;
+use crate::seq::SeqInput;
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+
+pub struct BinaryHeapInput<V = Verbatim>(SeqInput<V>);
+
+impl<V> Parse for BinaryHeapInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(BinaryHeapInput(input.parse()?))
+    }
+}
+
+impl<V> BinaryHeapInput<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        self.output(false)
+    }
+
+    /// Generates a min-heap, by wrapping every item in `std::cmp::Reverse`
+    pub fn into_min_heap_output(self) -> TokenStream {
+        self.output(true)
+    }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+
+    pub fn into_try_min_heap_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_min_heap_output)
+    }
+
+    fn output(self, reverse: bool) -> TokenStream {
+        let alloc = alloc_crate();
+        let target = Ident::new("items", Span::call_site());
+        let mut capacity = Capacity::default();
+        let updates: Vec<_> = self
+            .0
+            .values()
+            .map(|element| {
+                element.updates(&mut |value, conditional| match value {
+                    Value::One(expr) => {
+                        let expr = capacity.add_one(expr, conditional);
+                        if reverse {
+                            quote! {
+                                #target.push(::core::cmp::Reverse(#expr));
+                            }
+                        } else {
+                            quote! {
+                                #target.push(#expr);
+                            }
+                        }
+                    }
+                    Value::Many(expr) => {
+                        let iter = capacity.add_many(expr, conditional);
+                        if reverse {
+                            quote! {
+                                #target.extend(::core::iter::Iterator::map(#iter, ::core::cmp::Reverse));
+                            }
+                        } else if V::EXTEND {
+                            quote! {
+                                #target.extend(#iter);
+                            }
+                        } else {
+                            quote! {
+                                for item in #iter {
+                                    #target.push(item);
+                                }
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        let (spreads, initial_capacity) = capacity.into_output();
+        // Collecting the items into a `Vec` first means that the heap can be built in
+        // linear time, re-using the `Vec`'s allocation.
+        quote! {{
+            #spreads
+            let mut #target = #alloc::vec::Vec::with_capacity(#initial_capacity);
+            #(#updates)*
+            #alloc::collections::BinaryHeap::from(#target)
+        }}
+    }
+}
+
+impl<V> MakeFallible for BinaryHeapInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.0.make_fallible(fallible);
+    }
+}
@@ -1,47 +1,111 @@
-use crate::key_value::KeyValueSeq;
-use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
-use crate::ParseRaw;
+use crate::directive::Directives;
+use crate::duplicates::{literal_duplicate_errors, DuplicatePolicy};
+use crate::exclusion::remove_keys;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::key_value::{
+    keyed_entries, keyed_key_value, optional_entry_updates, spread_entry_updates, KeyValue,
+    KeyValueIterExpr, KeyValueSeq,
+};
+use crate::krate::alloc_crate;
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
+use crate::zip::ZipPolicy;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::parse;
+use syn::parse::{self, Parse, ParseStream};
 
-pub struct BTreeMapInput<V = Verbatim>(KeyValueSeq<V>);
+pub struct BTreeMapInput<V = Verbatim> {
+    duplicates: DuplicatePolicy,
+    zip: ZipPolicy,
+    key_values: KeyValueSeq<V>,
+    duplicate_errors: TokenStream,
+}
 
 impl<V> BTreeMapInput<V>
 where
+    V: Conversion,
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
         let target = Ident::new("map", Span::call_site());
-        let updates = self.0.key_values().map(|kv| {
-            let key = kv.key();
-            let value = kv.value();
-            match key {
-                Value::One(expr) => quote! {
-                    #target.insert(#expr, #value);
+        let duplicates = &self.duplicates;
+        let updates = self.key_values.key_values().map(|element| {
+            element.updates(&mut |kv, _| match kv {
+                KeyValue::Entry { key, value } => match key {
+                    Value::One(expr) => duplicates.insert(&target, expr, value),
+                    Value::Many(expr) => spread_entry_updates(expr, value, |value| {
+                        duplicates.insert(&target, quote!(key), value)
+                    }),
                 },
-                Value::Many(expr) => quote! {
-                    for key in #expr {
-                        #target.insert(key, #value);
+                KeyValue::Many(pairs) => duplicates.insert_pairs(&target, pairs, V::EXTEND),
+                KeyValue::Keyed { values, key } => {
+                    let entries = keyed_entries(values, key);
+                    let (key, value) = keyed_key_value::<V>(key);
+                    let insert = duplicates.insert(&target, key, value);
+                    quote! {
+                        for (key, value) in #entries {
+                            #insert
+                        }
                     }
-                },
-            }
+                }
+                KeyValue::Remove(keys) => remove_keys(&target, keys),
+                KeyValue::Optional { key, value } => {
+                    optional_entry_updates(key, value, |key, value| {
+                        duplicates.insert(&target, key, value)
+                    })
+                }
+                KeyValue::Zip { keys, values } => {
+                    let entries = self.zip.entries(&keys.expr, &values.expr, keys, values);
+                    duplicates.insert_pairs(&target, entries, V::EXTEND)
+                }
+            })
         });
+        let prelude = duplicates.prelude();
+        let duplicate_errors = &self.duplicate_errors;
         quote! {{
-            let mut #target = ::std::collections::BTreeMap::new();
+            #duplicate_errors
+            #prelude
+            let mut #target = #alloc::collections::BTreeMap::new();
             #(#updates)*
             #target
         }}
     }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
 }
 
-impl<V> ParseRaw for BTreeMapInput<V>
+impl<V> Parse for BTreeMapInput<V>
 where
-    ValueExpr<V>: ToTokens,
-    ValueIterExpr<V>: ToTokens,
+    Value<V>: Parse,
+    ValueExpr<V>: Parse,
 {
-    fn parse_raw(input: TokenStream) -> parse::Result<Self> {
-        Ok(BTreeMapInput(KeyValueSeq::parse_raw(input)?))
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let duplicates = DuplicatePolicy::for_map(&mut directives)?;
+        let zip = ZipPolicy::from_directives(&mut directives)?;
+        directives.finish()?;
+        let key_values: KeyValueSeq<V> = input.parse()?;
+        let duplicate_errors = if duplicates.rejects_literal_duplicates() {
+            literal_duplicate_errors(key_values.single_keys(), "key")
+        } else {
+            TokenStream::new()
+        };
+        Ok(BTreeMapInput {
+            duplicates,
+            zip,
+            key_values,
+            duplicate_errors,
+        })
+    }
+}
+
+impl<V> MakeFallible for BTreeMapInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.duplicates.make_fallible(fallible);
+        self.key_values.make_fallible(fallible);
     }
 }
@@ -1,17 +1,35 @@
-use crate::seq::SeqInput;
+use crate::directive::Directives;
+use crate::duplicates::{literal_duplicate_errors, DuplicatePolicy};
+use crate::exclusion::remove_items;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::alloc_crate;
+use crate::set_value::{SetSeq, SetValue};
 use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::parse::{self, Parse, ParseStream};
 
-pub struct BTreeSetInput<V = Verbatim>(SeqInput<V>);
+pub struct BTreeSetInput<V = Verbatim> {
+    duplicates: DuplicatePolicy,
+    values: SetSeq<V>,
+    duplicate_errors: TokenStream,
+}
 
 impl<V> Parse for BTreeSetInput<V>
 where
     Value<V>: Parse,
 {
     fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
-        Ok(BTreeSetInput(input.parse()?))
+        let mut directives: Directives = input.parse()?;
+        let duplicates = DuplicatePolicy::for_set(&mut directives)?;
+        directives.finish()?;
+        let values: SetSeq<V> = input.parse()?;
+        let duplicate_errors = literal_duplicate_errors(values.single_items(), "item");
+        Ok(BTreeSetInput {
+            duplicates,
+            values,
+            duplicate_errors,
+        })
     }
 }
 
@@ -21,21 +39,39 @@ where
     ValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
         let target = Ident::new("set", Span::call_site());
-        let updates = self.0.values().map(|value| match value {
-            Value::One(expr) => quote! {
-                #target.insert(#expr);
-            },
-            Value::Many(expr) => quote! {
-                for item in #expr {
-                    #target.insert(item);
+        let duplicates = &self.duplicates;
+        let updates = self.values.values().map(|element| {
+            element.updates(&mut |value, _| match value {
+                SetValue::Insert(Value::One(expr)) => duplicates.insert_item(&target, expr),
+                SetValue::Insert(Value::Many(expr)) => {
+                    let insert = duplicates.insert_item(&target, quote!(item));
+                    quote! {
+                        for item in #expr {
+                            #insert
+                        }
+                    }
                 }
-            },
+                SetValue::Remove(items) => remove_items(&target, items),
+            })
         });
+        let duplicate_errors = &self.duplicate_errors;
         quote! {{
-            let mut #target = ::std::collections::BTreeSet::new();
+            #duplicate_errors
+            let mut #target = #alloc::collections::BTreeSet::new();
             #(#updates)*
             #target
         }}
     }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+}
+
+impl<V> MakeFallible for BTreeSetInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.values.make_fallible(fallible);
+    }
 }
@@ -0,0 +1,270 @@
+use crate::capacity::Capacity;
+use crate::krate::alloc_crate;
+use crate::seq::SeqInput;
+use crate::value::{Value, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::{self, Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprCall, ExprLit, ExprPath, Lit, LitByteStr, LitStr};
+
+/// The input of `bytes!`, which creates a `Vec<u8>` from bytes, byte strings, encoded
+/// integers and spreads.
+pub struct BytesInput(SeqInput<Verbatim>);
+
+impl Parse for BytesInput {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(BytesInput(input.parse()?))
+    }
+}
+
+/// A single item of a `bytes!` input, which may be more than one byte.
+enum Bytes<'a> {
+    /// A byte string literal, `b"abc"`.
+    Str(&'a LitByteStr),
+    /// A hex string, `hex("0a ff")`, which has been decoded.
+    Hex(Vec<u8>),
+    /// An integer or float, encoded with a given byte order, such as `u16_le(n)`.
+    Encoded {
+        ty: Ident,
+        order: Ident,
+        len: usize,
+        arg: &'a Expr,
+    },
+    /// A single `u8`.
+    Byte(&'a Expr),
+}
+
+impl<'a> Bytes<'a> {
+    fn from_expr(expr: &'a Expr) -> parse::Result<Self> {
+        match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::ByteStr(lit),
+                ..
+            }) => Ok(Bytes::Str(lit)),
+            Expr::Call(ExprCall { func, args, .. }) => match call_name(func) {
+                Some(name) if name == "hex" => Ok(Bytes::Hex(hex_arg(expr, args)?)),
+                Some(name) => match encoder(&name) {
+                    Some((ty, order, len)) if args.len() == 1 => Ok(Bytes::Encoded {
+                        ty: Ident::new(ty, name.span()),
+                        order: Ident::new(&format!("to_{}_bytes", order), name.span()),
+                        len,
+                        arg: &args[0],
+                    }),
+                    Some(_) => Err(syn::Error::new(
+                        expr.span(),
+                        format!("`{}` takes one argument", name),
+                    )),
+                    None => Ok(Bytes::Byte(expr)),
+                },
+                None => Ok(Bytes::Byte(expr)),
+            },
+            _ => Ok(Bytes::Byte(expr)),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Bytes::Str(lit) => lit.value().len(),
+            Bytes::Hex(bytes) => bytes.len(),
+            Bytes::Encoded { len, .. } => *len,
+            Bytes::Byte(_) => 1,
+        }
+    }
+
+    /// Generates the statements that add the bytes to `target`. Any expressions are
+    /// added to `capacity`, so that they are evaluated in source order.
+    fn update(&self, target: &Ident, capacity: &mut Capacity, conditional: bool) -> TokenStream {
+        match self {
+            Bytes::Str(lit) => quote! {
+                #target.extend_from_slice(#lit);
+            },
+            Bytes::Hex(bytes) => quote! {
+                #target.extend_from_slice(&[#(#bytes),*]);
+            },
+            Bytes::Encoded { ty, order, arg, .. } => {
+                let encoded = quote_spanned!(arg.span() => #ty::#order(#arg));
+                let encoded = capacity.add_uncounted(encoded, conditional);
+                quote! {
+                    #target.extend_from_slice(&#encoded);
+                }
+            }
+            Bytes::Byte(expr) => {
+                let expr = capacity.add_uncounted(expr, conditional);
+                quote! {
+                    #target.push(#expr);
+                }
+            }
+        }
+    }
+}
+
+impl BytesInput {
+    pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        let target = Ident::new("bytes", Span::call_site());
+        let mut capacity = Capacity::default();
+        let mut fixed_len = 0;
+        let mut errors = TokenStream::new();
+        let updates: Vec<_> = self
+            .0
+            .values()
+            .map(|element| {
+                element.updates(&mut |value, conditional| match value {
+                    Value::One(expr) => match Bytes::from_expr(&expr.expr) {
+                        Ok(bytes) => {
+                            if !conditional {
+                                fixed_len += bytes.len();
+                            }
+                            bytes.update(&target, &mut capacity, conditional)
+                        }
+                        Err(error) => {
+                            errors.extend(error.to_compile_error());
+                            TokenStream::new()
+                        }
+                    },
+                    Value::Many(expr) => {
+                        let iter = capacity.add_many(expr, conditional);
+                        quote! {
+                            #target.extend(#iter);
+                        }
+                    }
+                })
+            })
+            .collect();
+        let (spreads, initial_capacity) = capacity.into_output();
+        quote! {{
+            #errors
+            #spreads
+            let mut #target: #alloc::vec::Vec<u8> =
+                #alloc::vec::Vec::with_capacity(#fixed_len + #initial_capacity);
+            #(#updates)*
+            #target
+        }}
+    }
+}
+
+/// Returns the name of a function that is called by its name alone, such as `hex`.
+fn call_name(func: &Expr) -> Option<Ident> {
+    match func {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => path.get_ident().cloned(),
+        _ => None,
+    }
+}
+
+/// Returns the type, byte order and length of an encoder, such as `u16_le`.
+fn encoder(name: &Ident) -> Option<(&'static str, &'static str, usize)> {
+    const TYPES: &[(&str, usize)] = &[
+        ("u16", 2),
+        ("u32", 4),
+        ("u64", 8),
+        ("u128", 16),
+        ("i16", 2),
+        ("i32", 4),
+        ("i64", 8),
+        ("i128", 16),
+        ("f32", 4),
+        ("f64", 8),
+    ];
+    let name = name.to_string();
+    let (ty, order) = name.split_once('_')?;
+    let (ty, len) = TYPES.iter().find(|(known, _)| *known == ty)?;
+    let order = match order {
+        "le" => "le",
+        "be" => "be",
+        _ => return None,
+    };
+    Some((ty, order, *len))
+}
+
+/// Decodes the argument of `hex("...")`, which must be a string literal of pairs of hex
+/// digits. Whitespace between the pairs is ignored.
+fn hex_arg(call: &Expr, args: &Punctuated<Expr, syn::Token![,]>) -> parse::Result<Vec<u8>> {
+    let lit = match args.first() {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        })) if args.len() == 1 => lit,
+        _ => {
+            return Err(syn::Error::new(
+                call.span(),
+                "`hex` takes one string literal, such as `hex(\"0a ff\")`",
+            ))
+        }
+    };
+    decode_hex(lit)
+}
+
+fn decode_hex(lit: &LitStr) -> parse::Result<Vec<u8>> {
+    let value = lit.value();
+    let mut bytes = Vec::new();
+    for pair in value.split_whitespace() {
+        if pair.len() % 2 != 0 {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("odd number of hex digits in `{}`", pair),
+            ));
+        }
+        for i in (0..pair.len()).step_by(2) {
+            let digits = pair.get(i..i + 2).unwrap_or(pair);
+            // `from_str_radix` also accepts a sign, such as in `+f`
+            let byte = Some(digits)
+                .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| {
+                    syn::Error::new(lit.span(), format!("invalid hex digits `{}`", digits))
+                })?;
+            bytes.push(byte);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_expr(input: &str) -> Expr {
+        syn::parse2(TokenStream::from_str(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn decode_hex_literal() {
+        let expr = parse_expr("hex(\"0aff 10\")");
+        assert!(
+            matches!(Bytes::from_expr(&expr), Ok(Bytes::Hex(bytes)) if bytes == [0x0a, 0xff, 0x10])
+        );
+    }
+
+    #[test]
+    fn invalid_hex_literal_is_error() {
+        assert!(Bytes::from_expr(&parse_expr("hex(\"0a f\")")).is_err());
+        assert!(Bytes::from_expr(&parse_expr("hex(\"zz\")")).is_err());
+        assert!(Bytes::from_expr(&parse_expr("hex(\"+f\")")).is_err());
+        assert!(Bytes::from_expr(&parse_expr("hex(\"-f\")")).is_err());
+        assert!(Bytes::from_expr(&parse_expr("hex(s)")).is_err());
+    }
+
+    #[test]
+    fn encoder_has_length_of_type() {
+        let expr = parse_expr("u32_be(n)");
+        assert!(matches!(
+            Bytes::from_expr(&expr),
+            Ok(Bytes::Encoded { len: 4, .. })
+        ));
+        assert!(matches!(
+            Bytes::from_expr(&parse_expr("u32_xx(n)")),
+            Ok(Bytes::Byte(_))
+        ));
+    }
+
+    #[test]
+    fn byte_string_has_length_of_string() {
+        let expr = parse_expr("b\"abc\"");
+        let bytes = Bytes::from_expr(&expr).unwrap();
+        assert_eq!(bytes.len(), 3);
+    }
+}
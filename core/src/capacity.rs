@@ -0,0 +1,95 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+
+/// Collects the values of a collection literal, in order to generate code that
+/// allocates the collection just once.
+///
+/// Values are evaluated in source order. Spreads are evaluated into iterators before
+/// the collection is allocated, and any values before a spread are bound to variables
+/// first, so that they are still evaluated before it. The initial capacity is then the
+/// number of single values plus the lower bounds of the iterators' size hints.
+///
+/// After the first value that is only added under some condition, or that must be
+/// evaluated in place, no more values are evaluated early, and the sizes of any later
+/// spreads are not counted.
+#[derive(Default)]
+pub(crate) struct Capacity {
+    singles: usize,
+    bindings: Vec<(Ident, TokenStream)>,
+    spreads: Vec<Ident>,
+    sizes: Vec<TokenStream>,
+    in_place: bool,
+}
+
+impl Capacity {
+    /// Adds a single value, returning an expression for it.
+    pub(crate) fn add_one(&mut self, expr: impl ToTokens, conditional: bool) -> TokenStream {
+        if !conditional {
+            self.singles += 1;
+        }
+        self.add_uncounted(expr, conditional)
+    }
+
+    /// Adds a value that doesn't count towards the capacity, such as the value of a map
+    /// entry, returning an expression for it.
+    pub(crate) fn add_uncounted(&mut self, expr: impl ToTokens, conditional: bool) -> TokenStream {
+        if conditional {
+            self.in_place = true;
+        }
+        if self.in_place {
+            return expr.into_token_stream();
+        }
+        let ident = format_ident!("value_{}", self.bindings.len(), span = Span::mixed_site());
+        self.bindings
+            .push((ident.clone(), expr.into_token_stream()));
+        ident.into_token_stream()
+    }
+
+    /// Adds a spread, returning an expression for its iterator.
+    pub(crate) fn add_many(&mut self, expr: impl ToTokens, conditional: bool) -> TokenStream {
+        if conditional {
+            self.in_place = true;
+        }
+        let iter = quote! {
+            ::core::iter::IntoIterator::into_iter(#expr)
+        };
+        if self.in_place {
+            return iter;
+        }
+        let ident = format_ident!("spread_{}", self.bindings.len(), span = Span::mixed_site());
+        self.bindings.push((ident.clone(), iter));
+        self.spreads.push(ident.clone());
+        ident.into_token_stream()
+    }
+
+    /// Adds an expression for the size of a value that was just added, such as the
+    /// length of a string, to the capacity. It is ignored if the value is evaluated in
+    /// place, since then it is not known when the collection is allocated.
+    pub(crate) fn add_size(&mut self, size: impl ToTokens) {
+        if !self.in_place {
+            self.sizes.push(size.into_token_stream());
+        }
+    }
+
+    /// Adds a value that must be evaluated in place, such as a removal. Later values
+    /// are evaluated in place too, so that they are still evaluated after it.
+    pub(crate) fn add_in_place(&mut self) {
+        self.in_place = true;
+    }
+
+    /// Returns statements that evaluate the values that are evaluated early, followed by
+    /// an expression for the initial capacity of the collection.
+    pub(crate) fn into_output(self) -> (TokenStream, TokenStream) {
+        let singles = self.singles;
+        let (idents, exprs): (Vec<_>, Vec<_>) = self.bindings.into_iter().unzip();
+        let spreads = self.spreads;
+        let sizes = self.sizes;
+        let bindings = quote! {
+            #(let #idents = #exprs;)*
+        };
+        let capacity = quote! {
+            #singles #(+ ::core::iter::Iterator::size_hint(&#spreads).0)* #(+ #sizes)*
+        };
+        (bindings, capacity)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::directive::Directives;
+use crate::hasher::Hasher;
+use crate::krate::{alloc_crate, HashCrate};
+use crate::seq::SeqInput;
+use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+use syn::{Expr, Token};
+
+/// The input of `counter!`, which creates a `HashMap<T, usize>` of the number of times
+/// each item occurs.
+pub struct CounterInput<V = Verbatim> {
+    hash_crate: HashCrate,
+    hasher: Hasher,
+    items: SeqInput<V, CounterItem<V>>,
+}
+
+/// The input of `btree_counter!`, which creates a `BTreeMap<T, usize>` of the number of
+/// times each item occurs.
+pub struct BTreeCounterInput<V = Verbatim>(SeqInput<V, CounterItem<V>>);
+
+/// An item to count, `item` or `..items`, optionally followed by the number of times
+/// to count it, `item: count`.
+pub struct CounterItem<V> {
+    value: Value<V>,
+    count: Option<Expr>,
+}
+
+impl<V> Parse for CounterItem<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let value = input.parse()?;
+        let count = if input.peek(Token![:]) {
+            let _: Token![:] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(CounterItem { value, count })
+    }
+}
+
+impl<V> Parse for CounterInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let hash_crate = HashCrate::from_directives(&mut directives)?;
+        let hasher = Hasher::from_directives(&mut directives)?;
+        directives.finish()?;
+        Ok(CounterInput {
+            hash_crate,
+            hasher,
+            items: input.parse()?,
+        })
+    }
+}
+
+impl<V> Parse for BTreeCounterInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let directives: Directives = input.parse()?;
+        directives.finish()?;
+        Ok(BTreeCounterInput(input.parse()?))
+    }
+}
+
+impl<V> CounterInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        // Only the count type is given, so that the hasher type comes from the hasher,
+        // or from `new` if there is none
+        let hash_map = self.hash_crate.collection("HashMap");
+        let hasher = self.hasher.binding();
+        let constructor = self
+            .hasher
+            .constructor(quote!(#hash_map::<_, usize, _>), None);
+        let constructor = quote! {{
+            #hasher
+            #constructor
+        }};
+        self.hash_crate
+            .require("HashMap", counter_output(&self.items, constructor))
+    }
+}
+
+impl<V> BTreeCounterInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        counter_output(
+            &self.0,
+            quote! {
+                #alloc::collections::BTreeMap::<_, usize>::new()
+            },
+        )
+    }
+}
+
+/// Generates the output of a counter macro, which counts the items into the map created
+/// by `constructor`.
+fn counter_output<V>(items: &SeqInput<V, CounterItem<V>>, constructor: TokenStream) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    let target = Ident::new("counter", Span::call_site());
+    let updates = items.values().map(|element| {
+        element.updates(&mut |CounterItem { value, count }, _| {
+            let count = match count {
+                Some(count) => count.into_token_stream(),
+                None => quote!(1),
+            };
+            match value {
+                Value::One(item) => quote! {
+                    *#target.entry(#item).or_insert(0) += #count;
+                },
+                Value::Many(items) => {
+                    // The count is evaluated once for all of the items, which must
+                    // not be visible to it
+                    let items_ident = Ident::new("items", Span::mixed_site());
+                    let count_ident = Ident::new("count", Span::mixed_site());
+                    quote! {{
+                        let #items_ident = ::core::iter::IntoIterator::into_iter(#items);
+                        let #count_ident: usize = #count;
+                        for item in #items_ident {
+                            *#target.entry(item).or_insert(0) += #count_ident;
+                        }
+                    }}
+                }
+            }
+        })
+    });
+    quote! {{
+        let mut #target = #constructor;
+        #(#updates)*
+        #target
+    }}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::Element;
+    use std::str::FromStr as _;
+
+    #[test]
+    fn parse_items_with_and_without_counts() {
+        let tokens = TokenStream::from_str("a, b: 3, ..c, ..d: n").unwrap();
+        let seq: SeqInput<Verbatim, CounterItem<Verbatim>> = syn::parse2(tokens).unwrap();
+        let items: Vec<_> = seq.values().collect();
+        assert_eq!(items.len(), 4);
+        assert!(matches!(
+            items[0],
+            Element::Value(CounterItem {
+                value: Value::One(_),
+                count: None
+            })
+        ));
+        assert!(matches!(
+            items[3],
+            Element::Value(CounterItem {
+                value: Value::Many(_),
+                count: Some(_)
+            })
+        ));
+    }
+}
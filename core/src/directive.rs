@@ -0,0 +1,183 @@
+use proc_macro2::TokenStream;
+use syn::parse::{self, Parse, ParseStream};
+use syn::{parenthesized, token, Ident, Token};
+
+/// A directive that changes how a macro generates its collection, either `@name` or
+/// `@name(arguments)`, followed by `;`.
+struct Directive {
+    name: Ident,
+    args: Option<TokenStream>,
+}
+
+/// The directives at the start of a macro's input, before any of its elements.
+///
+/// Each macro takes the directives that it supports, and then calls `finish` to report
+/// any others as errors.
+pub(crate) struct Directives(Vec<Directive>);
+
+impl Parse for Directives {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Vec<Directive> = Vec::new();
+        while input.peek(Token![@]) {
+            let _: Token![@] = input.parse()?;
+            let name: Ident = input.parse()?;
+            let args = if input.peek(token::Paren) {
+                let content;
+                parenthesized!(content in input);
+                Some(content.parse()?)
+            } else {
+                None
+            };
+            let _: Token![;] = input.parse()?;
+            if directives.iter().any(|directive| directive.name == name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("duplicate directive `@{}`", name),
+                ));
+            }
+            directives.push(Directive { name, args });
+        }
+        Ok(Directives(directives))
+    }
+}
+
+impl Directives {
+    fn remove(&mut self, name: &str) -> Option<Directive> {
+        let index = self.0.iter().position(|directive| directive.name == name)?;
+        Some(self.0.remove(index))
+    }
+
+    /// Takes the directive with the given name, if there is one, parsing its arguments
+    /// as a `T`.
+    pub(crate) fn take<T>(&mut self, name: &str) -> parse::Result<Option<T>>
+    where
+        T: Parse,
+    {
+        match self.remove(name) {
+            Some(Directive {
+                args: Some(args), ..
+            }) => syn::parse2(args).map(Some),
+            Some(Directive { name, args: None }) => Err(syn::Error::new(
+                name.span(),
+                format!("expected arguments: `@{}(...)`", name),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes the directive with the given name, which must not have arguments,
+    /// returning true if there was one.
+    pub(crate) fn take_flag(&mut self, name: &str) -> parse::Result<bool> {
+        match self.remove(name) {
+            Some(Directive {
+                name,
+                args: Some(_),
+            }) => Err(syn::Error::new(
+                name.span(),
+                format!("unexpected arguments: `@{}` does not take any", name),
+            )),
+            Some(_) => Ok(true),
+            None => Ok(false),
+        }
+    }
+
+    /// Checks that at most one of the named directives is present.
+    pub(crate) fn exclusive(&self, names: &[&str]) -> parse::Result<()> {
+        let mut present = self
+            .0
+            .iter()
+            .filter(|directive| names.iter().any(|name| directive.name == name));
+        match (present.next(), present.next()) {
+            (Some(first), Some(second)) => Err(syn::Error::new(
+                second.name.span(),
+                format!(
+                    "`@{}` can't be used together with `@{}`",
+                    second.name, first.name
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that all of the directives have been taken, since any others are not
+    /// supported by the macro.
+    pub(crate) fn finish(self) -> parse::Result<()> {
+        match self.0.into_iter().next() {
+            Some(Directive { name, .. }) => Err(syn::Error::new(
+                name.span(),
+                format!("unsupported directive `@{}`", name),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+    use syn::Expr;
+
+    fn parse_directives(input: &str) -> parse::Result<Directives> {
+        syn::parse2(TokenStream::from_str(input).unwrap())
+    }
+
+    #[test]
+    fn parse_no_directives() {
+        let directives = parse_directives("").unwrap();
+        assert!(directives.finish().is_ok());
+    }
+
+    #[test]
+    fn take_directive_with_arguments() {
+        let mut directives = parse_directives("@a(b + c); @d(e);").unwrap();
+        assert!(matches!(
+            directives.take::<Expr>("a"),
+            Ok(Some(Expr::Binary(_)))
+        ));
+        assert!(matches!(
+            directives.take::<Expr>("d"),
+            Ok(Some(Expr::Path(_)))
+        ));
+        assert!(matches!(directives.take::<Expr>("a"), Ok(None)));
+        assert!(directives.finish().is_ok());
+    }
+
+    #[test]
+    fn directive_without_semicolon_is_error() {
+        assert!(parse_directives("@a(b)").is_err());
+    }
+
+    #[test]
+    fn duplicate_directive_is_error() {
+        assert!(parse_directives("@a(b); @a(c);").is_err());
+    }
+
+    #[test]
+    fn missing_arguments_is_error() {
+        let mut directives = parse_directives("@a;").unwrap();
+        assert!(directives.take::<Expr>("a").is_err());
+    }
+
+    #[test]
+    fn take_flag_directive() {
+        let mut directives = parse_directives("@a; @b(c);").unwrap();
+        assert!(matches!(directives.take_flag("a"), Ok(true)));
+        assert!(matches!(directives.take_flag("a"), Ok(false)));
+        assert!(directives.take_flag("b").is_err());
+    }
+
+    #[test]
+    fn exclusive_directives_together_is_error() {
+        let directives = parse_directives("@a; @b; @c(d);").unwrap();
+        assert!(directives.exclusive(&["a", "x"]).is_ok());
+        assert!(directives.exclusive(&["a", "c"]).is_err());
+    }
+
+    #[test]
+    fn untaken_directive_is_error() {
+        let mut directives = parse_directives("@a(b); @c;").unwrap();
+        assert!(directives.take::<Expr>("a").is_ok());
+        assert!(directives.finish().is_err());
+    }
+}
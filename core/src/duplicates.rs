@@ -0,0 +1,257 @@
+use crate::directive::Directives;
+use crate::fallible::{Fallible, MakeFallible};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse;
+use syn::Expr;
+
+/// How a map or set literal handles a key that is added more than once.
+pub(crate) enum DuplicatePolicy {
+    /// The last value for the key replaces any earlier ones. This is the default.
+    KeepLast,
+    /// The first value for the key is kept, `@keep_first`.
+    KeepFirst,
+    /// Adding a key more than once panics, `@unique`.
+    Unique,
+    /// The values are combined by a function, `@merge(|old, new| ...)`.
+    Merge(Expr),
+}
+
+impl DuplicatePolicy {
+    /// Takes the policy for a map from the macro's directives.
+    pub(crate) fn for_map(directives: &mut Directives) -> parse::Result<Self> {
+        directives.exclusive(&["keep_first", "unique", "merge"])?;
+        Ok(if directives.take_flag("keep_first")? {
+            DuplicatePolicy::KeepFirst
+        } else if directives.take_flag("unique")? {
+            DuplicatePolicy::Unique
+        } else if let Some(merge) = directives.take("merge")? {
+            DuplicatePolicy::Merge(merge)
+        } else {
+            DuplicatePolicy::KeepLast
+        })
+    }
+
+    /// Takes the policy for a set from the macro's directives. Only `@unique` is
+    /// supported, since a set keeps the first of any equal items.
+    pub(crate) fn for_set(directives: &mut Directives) -> parse::Result<Self> {
+        Ok(if directives.take_flag("unique")? {
+            DuplicatePolicy::Unique
+        } else {
+            DuplicatePolicy::KeepFirst
+        })
+    }
+
+    /// Returns true if entries can be added to the map with `Extend`, which replaces
+    /// the values of existing keys.
+    fn can_extend(&self) -> bool {
+        matches!(self, DuplicatePolicy::KeepLast)
+    }
+
+    /// Returns true if literal keys that are given more than once are an error,
+    /// which is the case unless the policy was chosen to handle them.
+    pub(crate) fn rejects_literal_duplicates(&self) -> bool {
+        matches!(self, DuplicatePolicy::KeepLast | DuplicatePolicy::Unique)
+    }
+
+    /// Generates the statements that must come before any items are added.
+    pub(crate) fn prelude(&self) -> TokenStream {
+        match self {
+            DuplicatePolicy::Merge(merge) => {
+                let merge_ident = merge_ident();
+                quote! {
+                    let #merge_ident = #merge;
+                }
+            }
+            _ => TokenStream::new(),
+        }
+    }
+
+    /// Generates a statement that inserts a key and value into the map `target`.
+    pub(crate) fn insert(
+        &self,
+        target: &Ident,
+        key: impl ToTokens,
+        value: impl ToTokens,
+    ) -> TokenStream {
+        match self {
+            DuplicatePolicy::KeepLast => quote! {
+                #target.insert(#key, #value);
+            },
+            DuplicatePolicy::KeepFirst => quote! {
+                #target.entry(#key).or_insert(#value);
+            },
+            DuplicatePolicy::Unique => {
+                // The key is bound before the value is evaluated, so it must not be
+                // visible to the value expression
+                let key_ident = Ident::new("key", Span::mixed_site());
+                quote! {{
+                    let #key_ident = #key;
+                    let value = #value;
+                    if #target.contains_key(&#key_ident) {
+                        ::core::panic!("duplicate key in map literal: {:?}", #key_ident);
+                    }
+                    #target.insert(#key_ident, value);
+                }}
+            }
+            DuplicatePolicy::Merge(_) => {
+                let key_ident = Ident::new("key", Span::mixed_site());
+                let merge_ident = merge_ident();
+                quote! {{
+                    let #key_ident = #key;
+                    let value = #value;
+                    let value = match #target.remove(&#key_ident) {
+                        ::core::option::Option::Some(old) => #merge_ident(old, value),
+                        ::core::option::Option::None => value,
+                    };
+                    #target.insert(#key_ident, value);
+                }}
+            }
+        }
+    }
+
+    /// Generates the statements that insert each `(key, value)` pair of an iterator into
+    /// the map `target`. `extend` is true if the pairs don't need to be converted, so
+    /// they can be inserted with `Extend` if the policy allows.
+    pub(crate) fn insert_pairs(
+        &self,
+        target: &Ident,
+        pairs: impl ToTokens,
+        extend: bool,
+    ) -> TokenStream {
+        if extend && self.can_extend() {
+            quote! {
+                #target.extend(#pairs);
+            }
+        } else {
+            let insert = self.insert(target, quote!(key), quote!(value));
+            quote! {
+                for (key, value) in #pairs {
+                    #insert
+                }
+            }
+        }
+    }
+
+    /// Generates a statement that inserts an item into the set `target`.
+    pub(crate) fn insert_item(&self, target: &Ident, item: impl ToTokens) -> TokenStream {
+        match self {
+            DuplicatePolicy::Unique => quote! {
+                if let ::core::option::Option::Some(item) = #target.replace(#item) {
+                    ::core::panic!("duplicate item in set literal: {:?}", item);
+                }
+            },
+            _ => quote! {
+                #target.insert(#item);
+            },
+        }
+    }
+}
+
+impl MakeFallible for DuplicatePolicy {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        if let DuplicatePolicy::Merge(merge) = self {
+            fallible.expr(merge);
+        }
+    }
+}
+
+/// A key or item that is always added to, or removed from, a map or set literal, as
+/// opposed to one that is spread or only added under some condition.
+pub(crate) enum SingleKey<'a> {
+    Added(&'a Expr),
+    Removed(&'a Expr),
+}
+
+/// Generates a compile error for each literal or path key that is the same, token for
+/// token, as an earlier one that has not been removed. The error points at the
+/// duplicate, and is followed by a second error pointing at the first key. `what`
+/// describes the keys in the error messages, such as `"key"`.
+///
+/// Other expressions are not compared, since they can have different values. The
+/// errors are returned as tokens to be included in the macro's output, rather than
+/// failing the parse, so that the rest of the output is still checked.
+pub(crate) fn literal_duplicate_errors<'a, I>(keys: I, what: &str) -> TokenStream
+where
+    I: IntoIterator<Item = SingleKey<'a>>,
+{
+    let mut seen: Vec<(String, &Expr)> = Vec::new();
+    let mut errors = TokenStream::new();
+    for key in keys {
+        let (key, removed) = match key {
+            SingleKey::Added(key) => (key, false),
+            SingleKey::Removed(key) => (key, true),
+        };
+        if !matches!(key, Expr::Lit(_) | Expr::Path(_)) {
+            continue;
+        }
+        let tokens = key.to_token_stream().to_string();
+        if removed {
+            seen.retain(|(seen, _)| *seen != tokens);
+            continue;
+        }
+        match seen.iter().find(|(seen, _)| *seen == tokens) {
+            Some((_, first)) => {
+                // A diagnostic with several spans can't be emitted on stable, so the
+                // first key gets an error of its own
+                let duplicate = format!("duplicate {} `{}`", what, tokens);
+                let first_given = format!("`{}` is first given here", tokens);
+                errors.extend(syn::Error::new_spanned(key, duplicate).to_compile_error());
+                errors.extend(syn::Error::new_spanned(first, first_given).to_compile_error());
+            }
+            None => seen.push((tokens, key)),
+        }
+    }
+    errors
+}
+
+fn merge_ident() -> Ident {
+    Ident::new("merge", Span::mixed_site())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_exprs(input: &str) -> Vec<Expr> {
+        let tokens = TokenStream::from_str(input).unwrap();
+        let parser = syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated;
+        syn::parse::Parser::parse2(parser, tokens)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn repeated_literal_is_error() {
+        let exprs = parse_exprs("'a', 'b', 'a'");
+        let errors =
+            literal_duplicate_errors(exprs.iter().map(SingleKey::Added), "key").to_string();
+        assert!(errors.contains("duplicate key"));
+        assert!(errors.contains("first given here"));
+    }
+
+    #[test]
+    fn repeated_path_is_error() {
+        let exprs = parse_exprs("A, b::C, b::C");
+        assert!(!literal_duplicate_errors(exprs.iter().map(SingleKey::Added), "key").is_empty());
+    }
+
+    #[test]
+    fn repeated_literal_after_removal_is_not_error() {
+        let exprs = parse_exprs("'a', 'a'");
+        let keys = vec![
+            SingleKey::Added(&exprs[0]),
+            SingleKey::Removed(&exprs[0]),
+            SingleKey::Added(&exprs[1]),
+        ];
+        assert!(literal_duplicate_errors(keys, "key").is_empty());
+    }
+
+    #[test]
+    fn repeated_other_expression_is_not_error() {
+        let exprs = parse_exprs("f(), f(), a + 1, a + 1");
+        assert!(literal_duplicate_errors(exprs.iter().map(SingleKey::Added), "key").is_empty());
+    }
+}
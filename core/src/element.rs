@@ -0,0 +1,356 @@
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::velcro_private;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+use syn::{Attribute, Expr, Pat, Token};
+
+/// An element of a macro's input, which is either a value of type `T`, or one of the
+/// structures that can contain other elements, such as a condition, a comprehension
+/// or an element with outer attributes.
+///
+/// `T` is the value type of the macro, for example a `Value` for a sequence or a
+/// `KeyValue` for a map. Conditions and comprehensions are elements, rather than kinds
+/// of `Value`, because they contain a whole item of the input, such as the map entry in
+/// `if tls => "port": 443`, while a `Value` is only the key or the value of an entry.
+pub enum Element<T> {
+    Value(T),
+    If(ElementIf<T>),
+    For(ElementFor<T>),
+    Attributed(ElementAttributed<T>),
+}
+
+/// A conditional element: `if condition => element`, optionally followed by
+/// `else element`.
+pub struct ElementIf<T> {
+    pub(crate) cond: Expr,
+    pub(crate) then: Box<Element<T>>,
+    pub(crate) otherwise: Option<Box<Element<T>>>,
+}
+
+/// A comprehension, which repeats an element for each combination of the items of
+/// one or more iterators, optionally filtered by conditions:
+/// `for pattern in iterator for ... if condition => element`.
+pub struct ElementFor<T> {
+    pub(crate) clauses: Vec<Clause>,
+    pub(crate) body: Box<Element<T>>,
+}
+
+/// An element with outer attributes, such as `#[cfg(feature = "x")] element`. The
+/// attributes are applied to the statements that add the element to the collection.
+pub struct ElementAttributed<T> {
+    pub(crate) attrs: Vec<Attribute>,
+    pub(crate) element: Box<Element<T>>,
+}
+
+/// A clause of a comprehension
+pub enum Clause {
+    For { pat: Pat, expr: Expr },
+    If(Expr),
+}
+
+impl<T> Element<T> {
+    /// Generates the statements that add the element to a collection, using `f` to
+    /// generate the statements for each value. The second argument to `f` is true if
+    /// the value is only added under some condition.
+    pub(crate) fn updates<F>(&self, f: &mut F) -> TokenStream
+    where
+        F: FnMut(&T, bool) -> TokenStream,
+    {
+        self.updates_inner(f, false)
+    }
+
+    fn updates_inner<F>(&self, f: &mut F, conditional: bool) -> TokenStream
+    where
+        F: FnMut(&T, bool) -> TokenStream,
+    {
+        match self {
+            Element::Value(value) => f(value, conditional),
+            Element::If(ElementIf {
+                cond,
+                then,
+                otherwise,
+            }) => {
+                let then = then.updates_inner(f, true);
+                let otherwise = otherwise.as_ref().map(|otherwise| {
+                    let otherwise = otherwise.updates_inner(f, true);
+                    quote! {
+                        else {
+                            #otherwise
+                        }
+                    }
+                });
+                quote! {
+                    if #cond {
+                        #then
+                    } #otherwise
+                }
+            }
+            Element::For(ElementFor { clauses, body }) => {
+                let body = body.updates_inner(f, true);
+                clauses
+                    .iter()
+                    .rev()
+                    .fold(body, |inner, clause| match clause {
+                        Clause::For { pat, expr } => quote! {
+                            for #pat in #expr {
+                                #inner
+                            }
+                        },
+                        Clause::If(cond) => quote! {
+                            if #cond {
+                                #inner
+                            }
+                        },
+                    })
+            }
+            Element::Attributed(ElementAttributed { attrs, element }) => {
+                // an attribute such as `#[cfg]` can remove the element entirely
+                let inner = element.updates_inner(f, true);
+                quote! {
+                    #(#attrs)*
+                    {
+                        #inner
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates an expression for an iterator over the items of the element, using
+    /// `f` to generate an expression of a type that implements `IntoIterator` for
+    /// each value.
+    pub(crate) fn to_iter<F>(&self, f: &mut F) -> TokenStream
+    where
+        F: FnMut(&T) -> TokenStream,
+    {
+        self.to_iter_inner(f, false)
+    }
+
+    /// The closures of comprehensions borrow the variables that they use, except for
+    /// those nested inside another comprehension's closure, which must move the
+    /// variables bound by the outer patterns, since they outlive each call.
+    fn to_iter_inner<F>(&self, f: &mut F, nested: bool) -> TokenStream
+    where
+        F: FnMut(&T) -> TokenStream,
+    {
+        match self {
+            Element::Value(value) => f(value),
+            Element::If(ElementIf {
+                cond,
+                then,
+                otherwise,
+            }) => {
+                let private = velcro_private();
+                let then = then.to_iter_inner(f, nested);
+                match otherwise {
+                    Some(otherwise) => {
+                        let otherwise = otherwise.to_iter_inner(f, nested);
+                        quote! {{
+                            let (then, otherwise) = if #cond {
+                                (::core::option::Option::Some(#then), ::core::option::Option::None)
+                            } else {
+                                (::core::option::Option::None, ::core::option::Option::Some(#otherwise))
+                            };
+                            ::core::iter::Iterator::chain(
+                                #private::optional(then),
+                                #private::optional(otherwise),
+                            )
+                        }}
+                    }
+                    None => quote! {
+                        #private::optional(
+                            if #cond {
+                                ::core::option::Option::Some(#then)
+                            } else {
+                                ::core::option::Option::None
+                            }
+                        )
+                    },
+                }
+            }
+            Element::For(ElementFor { clauses, body }) => {
+                let body = body.to_iter_inner(f, true);
+                clauses
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .fold(body, |inner, (i, clause)| match clause {
+                        Clause::For { pat, expr } => {
+                            let pat = closure_param(pat);
+                            let capture = if nested || i > 0 {
+                                quote!(move)
+                            } else {
+                                TokenStream::new()
+                            };
+                            quote! {
+                                ::core::iter::Iterator::flat_map(
+                                    ::core::iter::IntoIterator::into_iter(#expr),
+                                    #capture |#pat| #inner,
+                                )
+                            }
+                        }
+                        Clause::If(cond) => {
+                            let private = velcro_private();
+                            quote! {
+                                #private::optional(
+                                    if #cond {
+                                        ::core::option::Option::Some(#inner)
+                                    } else {
+                                        ::core::option::Option::None
+                                    }
+                                )
+                            }
+                        }
+                    })
+            }
+            Element::Attributed(ElementAttributed { attrs, element }) => {
+                let inner = element.to_iter_inner(f, nested);
+                quote! {{
+                    let iter = ::core::iter::empty();
+                    #(#attrs)*
+                    let iter = ::core::iter::Iterator::chain(iter, #inner);
+                    iter
+                }}
+            }
+        }
+    }
+}
+
+impl<T> MakeFallible for Element<T>
+where
+    T: MakeFallible,
+{
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            Element::Value(value) => value.make_fallible(fallible),
+            Element::If(ElementIf {
+                cond,
+                then,
+                otherwise,
+            }) => {
+                fallible.expr(cond);
+                then.make_fallible(fallible);
+                if let Some(otherwise) = otherwise {
+                    otherwise.make_fallible(fallible);
+                }
+            }
+            Element::For(ElementFor { clauses, body }) => {
+                for clause in clauses {
+                    match clause {
+                        Clause::For { expr, .. } => fallible.expr(expr),
+                        Clause::If(cond) => fallible.expr(cond),
+                    }
+                }
+                body.make_fallible(fallible);
+            }
+            Element::Attributed(ElementAttributed { element, .. }) => {
+                element.make_fallible(fallible)
+            }
+        }
+    }
+}
+
+/// Or-patterns must be wrapped in parentheses to be used as a closure parameter
+fn closure_param(pat: &Pat) -> TokenStream {
+    match pat {
+        Pat::Or(_) => quote!((#pat)),
+        _ => pat.into_token_stream(),
+    }
+}
+
+impl<T> Parse for Element<T>
+where
+    T: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        if !attrs.is_empty() {
+            Ok(Element::Attributed(ElementAttributed {
+                attrs,
+                element: Box::new(input.parse()?),
+            }))
+        } else if peek_condition(input) {
+            Ok(Element::If(input.parse()?))
+        } else if peek_comprehension(input) {
+            Ok(Element::For(input.parse()?))
+        } else {
+            Ok(Element::Value(input.parse()?))
+        }
+    }
+}
+
+impl<T> Parse for ElementIf<T>
+where
+    T: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let _: Token![if] = input.parse()?;
+        let cond = Expr::parse_without_eager_brace(input)?;
+        let _: Token![=>] = input.parse()?;
+        let then = Box::new(input.parse()?);
+        let otherwise = if input.peek(Token![else]) {
+            let _: Token![else] = input.parse()?;
+            Some(Box::new(input.parse()?))
+        } else {
+            None
+        };
+        Ok(ElementIf {
+            cond,
+            then,
+            otherwise,
+        })
+    }
+}
+
+impl<T> Parse for ElementFor<T>
+where
+    T: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let clauses = parse_clauses(input)?;
+        let _: Token![=>] = input.parse()?;
+        Ok(ElementFor {
+            clauses,
+            body: Box::new(input.parse()?),
+        })
+    }
+}
+
+/// Parses `for pattern in iterator`, followed by any number of other `for` or
+/// `if condition` clauses.
+fn parse_clauses(input: ParseStream) -> parse::Result<Vec<Clause>> {
+    let mut clauses = Vec::new();
+    loop {
+        if input.peek(Token![for]) {
+            let _: Token![for] = input.parse()?;
+            let pat = Pat::parse_multi_with_leading_vert(input)?;
+            let _: Token![in] = input.parse()?;
+            let expr = Expr::parse_without_eager_brace(input)?;
+            clauses.push(Clause::For { pat, expr });
+        } else if input.peek(Token![if]) && !clauses.is_empty() {
+            let _: Token![if] = input.parse()?;
+            clauses.push(Clause::If(Expr::parse_without_eager_brace(input)?));
+        } else if clauses.is_empty() {
+            return Err(input.error("expected `for`"));
+        } else {
+            return Ok(clauses);
+        }
+    }
+}
+
+/// Checks if the input starts with `for pattern in iterator ... =>`. Without the `=>`
+/// it is an ordinary `for` loop expression, which is parsed as a value.
+fn peek_comprehension(input: ParseStream) -> bool {
+    let fork = input.fork();
+    parse_clauses(&fork).is_ok() && fork.peek(Token![=>])
+}
+
+/// Checks if the input starts with `if condition =>`. Without the `=>` it is an
+/// ordinary `if` expression, which is parsed as a value.
+fn peek_condition(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![if]>().is_ok()
+        && Expr::parse_without_eager_brace(&fork).is_ok()
+        && fork.peek(Token![=>])
+}
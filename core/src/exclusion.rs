@@ -0,0 +1,49 @@
+use crate::krate::velcro_private;
+use crate::value::{Value, ValueExpr, ValueIterExpr};
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+
+/// Generates the statements that remove an item, `~item`, or many items, `~..items`,
+/// from the set `target`.
+pub(crate) fn remove_items<V>(target: &Ident, items: &Value<V>) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    // The items must have the same type as the set's items, which can't be inferred
+    // from `remove` if they are converted. The check is a function of `velcro`, so that
+    // it can't be shadowed by, or shadow, a function that the items call.
+    let private = velcro_private();
+    remove(target, items, quote!(#private::same_item_type))
+}
+
+/// Generates the statements that remove the entry for a key, `~key`, or for many keys,
+/// `~..keys`, from the map `target`.
+pub(crate) fn remove_keys<V>(target: &Ident, keys: &Value<V>) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    let private = velcro_private();
+    remove(target, keys, quote!(#private::same_key_type))
+}
+
+fn remove<V>(target: &Ident, value: &Value<V>, same_type: TokenStream) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    match value {
+        Value::One(expr) => quote! {{
+            let item = #expr;
+            #same_type(&#target, &item);
+            #target.remove(&item);
+        }},
+        Value::Many(expr) => quote! {{
+            for item in #expr {
+                #same_type(&#target, &item);
+                #target.remove(&item);
+            }
+        }},
+    }
+}
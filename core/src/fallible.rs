@@ -0,0 +1,273 @@
+use crate::krate::{alloc_crate, velcro_private};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::parse::{self, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Expr, ExprParen, ExprTry, Item, Lifetime, Macro, Token};
+
+/// Implemented by the inputs of macros that have a `try_` variant, and by their parts.
+pub(crate) trait MakeFallible {
+    /// Rewrites every `?` operator in the input's expressions using `fallible`.
+    fn make_fallible(&mut self, fallible: &mut Fallible);
+}
+
+/// Rewrites the expressions of a `try_` macro so that the `?` operator breaks out of
+/// a labeled block around the macro's output, instead of returning from the
+/// enclosing function. The whole macro then evaluates to a `Result`.
+///
+/// A `?` inside a closure or an async block still applies to that closure or block.
+pub(crate) struct Fallible;
+
+/// The label of the block around the output of a `try_` macro. It has a mixed-site
+/// span, so it can't be named by the macro's input.
+fn label() -> Lifetime {
+    Lifetime::new("'velcro", Span::mixed_site())
+}
+
+/// Generates an expression that unwraps the value of `?`, with the `Try` trait from the
+/// `velcro` crate, which is implemented for `Result` and `Option`. Otherwise, it breaks
+/// out of the block around the output of a `try_` macro with the residual, such as an
+/// error converted with `From`.
+pub(crate) fn break_on_error(result: impl ToTokens, span: Span) -> TokenStream {
+    break_on_output(branch(result, span), span)
+}
+
+/// Generates an expression that unwraps a `Result` whose error is the output of a `try_`
+/// macro, or breaks out of the block around the output with it.
+pub(crate) fn break_on_output(result: impl ToTokens, span: Span) -> TokenStream {
+    let label = label();
+    quote_spanned! {
+        span =>
+        match #result {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(output) => break #label output,
+        }
+    }
+}
+
+/// Generates an expression that maps a value with `?` to a `Result` whose error is the
+/// output of a `try_` macro, so that it can be broken out with using
+/// [`break_on_output`].
+pub(crate) fn branch(value: impl ToTokens, span: Span) -> TokenStream {
+    let private = velcro_private();
+    quote_spanned!(span => #private::branch(#value))
+}
+
+/// Generates an expression that collects an iterator of `Result`s, whose errors are the
+/// output of a `try_` macro, into a `Result` of a `Vec`, stopping at the first error.
+pub(crate) fn collect_results(iter: impl ToTokens, span: Span) -> TokenStream {
+    let alloc = alloc_crate();
+    quote_spanned! {
+        span =>
+        ::core::iter::Iterator::collect::<
+            ::core::result::Result<#alloc::vec::Vec<_>, _>
+        >(::core::iter::IntoIterator::into_iter(#iter))
+    }
+}
+
+impl Fallible {
+    /// Generates the output of a `try_` macro, from its input and the function that
+    /// generates the output of the infallible macro.
+    pub(crate) fn output<T, F>(mut input: T, into_output: F) -> TokenStream
+    where
+        T: MakeFallible,
+        F: FnOnce(T) -> TokenStream,
+    {
+        input.make_fallible(&mut Fallible);
+        let output = into_output(input);
+        let label = label();
+        let private = velcro_private();
+        quote! {
+            #label: {
+                #private::Try::from_output(#output)
+            }
+        }
+    }
+
+    /// Rewrites the `?` operators in an expression.
+    pub(crate) fn expr(&mut self, expr: &mut Expr) {
+        self.visit_expr_mut(expr);
+    }
+
+    /// Rewrites the `?` operators in the expression of a spread. A `?` that applies to
+    /// the whole spread collects an iterator of `Result`s, stopping at the first error.
+    pub(crate) fn spread(&mut self, expr: &mut Expr) {
+        if let Expr::Try(ExprTry {
+            expr: iter,
+            question_token,
+            ..
+        }) = expr
+        {
+            self.visit_expr_mut(iter);
+            // The parentheses in `..(iter)?` are not needed once the `?` is removed
+            let iter = match &**iter {
+                Expr::Paren(ExprParen { expr, .. }) => expr,
+                iter => iter,
+            };
+            let private = velcro_private();
+            let results = quote_spanned! {
+                iter.span() =>
+                ::core::iter::Iterator::map(
+                    ::core::iter::IntoIterator::into_iter(#iter),
+                    #private::branch,
+                )
+            };
+            let collected = collect_results(results, iter.span());
+            *expr = Expr::Verbatim(break_on_output(collected, question_token.span));
+        } else {
+            self.visit_expr_mut(expr);
+        }
+    }
+}
+
+impl VisitMut for Fallible {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Closure(_) | Expr::Async(_) | Expr::TryBlock(_) => {}
+            Expr::Try(ExprTry {
+                expr: result,
+                question_token,
+                ..
+            }) => {
+                self.visit_expr_mut(result);
+                // syn would parse `break 'label ::path` as a break with a labeled block,
+                // so the expression is kept as tokens
+                *expr = Expr::Verbatim(break_on_error(&result, question_token.span));
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    // The arguments of a macro are rewritten if they are expressions, as in `format!`
+    // and `vec!`. Otherwise, a `?` in them is an error, since it would return from the
+    // enclosing function
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        let question_span = match find_question_mark(mac.tokens.clone()) {
+            Some(span) => span,
+            None => return,
+        };
+        match mac.parse_body_with(MacroArgs::parse) {
+            Ok(mut args) => {
+                for expr in args.exprs.iter_mut() {
+                    self.visit_expr_mut(expr);
+                }
+                mac.tokens = args.into_token_stream();
+            }
+            Err(_) => {
+                let message = "the `?` operator can only be used in the arguments of a macro \
+                               in a `try_` macro if they are expressions";
+                mac.path = syn::parse_quote_spanned!(question_span => ::core::compile_error);
+                mac.tokens = quote_spanned!(question_span => #message);
+            }
+        }
+    }
+
+    // Nested items, such as functions, have their own `?` scope
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+/// Returns the span of the first `?` in some tokens, including in groups.
+fn find_question_mark(tokens: TokenStream) -> Option<Span> {
+    tokens.into_iter().find_map(|token| match token {
+        TokenTree::Punct(punct) if punct.as_char() == '?' => Some(punct.span()),
+        TokenTree::Group(group) => find_question_mark(group.stream()),
+        _ => None,
+    })
+}
+
+/// The arguments of a macro that are expressions: comma-delimited, as in `format!`, or
+/// a value and a length, as in `vec![value; len]`.
+struct MacroArgs {
+    exprs: Punctuated<Expr, TokenTree>,
+}
+
+impl MacroArgs {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut exprs = Punctuated::new();
+        while !input.is_empty() {
+            exprs.push_value(input.parse()?);
+            if input.is_empty() {
+                break;
+            }
+            let lookahead = input.lookahead1();
+            if lookahead.peek(Token![,]) || lookahead.peek(Token![;]) {
+                exprs.push_punct(input.parse()?);
+            } else {
+                return Err(lookahead.error());
+            }
+        }
+        Ok(MacroArgs { exprs })
+    }
+}
+
+impl ToTokens for MacroArgs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.exprs.to_tokens(tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn rewrite(input: &str, f: impl FnOnce(&mut Fallible, &mut Expr)) -> String {
+        let mut expr: Expr = syn::parse2(TokenStream::from_str(input).unwrap()).unwrap();
+        f(&mut Fallible, &mut expr);
+        expr.into_token_stream().to_string()
+    }
+
+    #[test]
+    fn try_operator_breaks_with_error() {
+        let output = rewrite("a(b?)", Fallible::expr);
+        assert!(!output.contains('?'));
+        assert!(output.contains("break 'velcro"));
+    }
+
+    #[test]
+    fn try_operator_in_closure_is_unchanged() {
+        let output = rewrite("a.map(|b| b?)", Fallible::expr);
+        assert!(output.contains('?'));
+        assert!(!output.contains("break"));
+    }
+
+    #[test]
+    fn try_operator_in_nested_fn_is_unchanged() {
+        let output = rewrite("{ fn a() -> Result<(), ()> { b? } a() }", Fallible::expr);
+        assert!(output.contains('?'));
+        assert!(!output.contains("break"));
+    }
+
+    #[test]
+    fn try_operator_in_macro_arguments_breaks_with_error() {
+        let output = rewrite("format!(\"{}\", a?)", Fallible::expr);
+        assert!(!output.contains('?'));
+        assert!(output.contains("break 'velcro"));
+        let output = rewrite("vec![a?; n]", Fallible::expr);
+        assert!(!output.contains('?'));
+        assert!(output.contains("break 'velcro"));
+    }
+
+    #[test]
+    fn try_operator_in_other_macro_tokens_is_error() {
+        let output = rewrite("m!(a? => b)", Fallible::expr);
+        assert!(output.contains("compile_error"));
+        assert!(!output.contains("break"));
+    }
+
+    #[test]
+    fn try_operator_on_spread_collects_results() {
+        let output = rewrite("a?", Fallible::spread);
+        assert!(output.contains("collect"));
+        assert!(output.contains("break 'velcro"));
+    }
+
+    #[test]
+    fn try_operator_inside_spread_does_not_collect() {
+        let output = rewrite("a?.b()", Fallible::spread);
+        assert!(!output.contains("collect"));
+        assert!(output.contains("break 'velcro"));
+    }
+}
@@ -1,58 +1,151 @@
-use crate::key_value::KeyValueSeq;
-use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
-use crate::ParseRaw;
+use crate::capacity::Capacity;
+use crate::directive::Directives;
+use crate::duplicates::{literal_duplicate_errors, DuplicatePolicy};
+use crate::exclusion::remove_keys;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::hasher::Hasher;
+use crate::key_value::{
+    keyed_entries, keyed_key_value, optional_entry_updates, spread_entry_updates, KeyValue,
+    KeyValueIterExpr, KeyValueSeq,
+};
+use crate::krate::HashCrate;
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
+use crate::zip::ZipPolicy;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::parse;
+use syn::parse::{self, Parse, ParseStream};
 
-pub struct HashMapInput<V = Verbatim>(KeyValueSeq<V>);
+pub struct HashMapInput<V = Verbatim> {
+    hash_crate: HashCrate,
+    hasher: Hasher,
+    duplicates: DuplicatePolicy,
+    zip: ZipPolicy,
+    key_values: KeyValueSeq<V>,
+    duplicate_errors: TokenStream,
+}
 
 impl<V> HashMapInput<V>
 where
+    V: Conversion,
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
         let target = Ident::new("map", Span::call_site());
-        let key_values = self.0.key_values();
-        let initial_capacity = if self.0.is_simple() {
-            key_values.len()
-        } else {
-            // A simple heuristic for the initial capacity. At this point we can guess that
-            // the output length is likely to be greater than the number of values, since
-            // at least one of the values is an iterator. This will reduce the number of
-            // allocations in common cases, while not massively over-allocating when the
-            // collection is small.
-            16.max(key_values.len().next_power_of_two() * 2)
-        };
-        let updates = self.0.key_values().map(|kv| {
-            let key = kv.key();
-            let value = kv.value();
-            match key {
-                Value::One(expr) => quote! {
-                    #target.insert(#expr, #value);
-                },
-                Value::Many(expr) => quote! {
-                    for key in #expr {
-                        #target.insert(key, #value);
+        let mut capacity = Capacity::default();
+        let duplicates = &self.duplicates;
+        let zip = self.zip;
+        let updates: Vec<_> = self
+            .key_values
+            .key_values()
+            .map(|element| {
+                element.updates(&mut |kv, conditional| match kv {
+                    KeyValue::Entry { key, value } => match key {
+                        Value::One(expr) => {
+                            let key = capacity.add_one(expr, conditional);
+                            let value = capacity.add_uncounted(value, conditional);
+                            duplicates.insert(&target, key, value)
+                        }
+                        Value::Many(expr) => {
+                            let iter = capacity.add_many(expr, conditional);
+                            // the value is evaluated after the keys
+                            capacity.add_in_place();
+                            spread_entry_updates(iter, value, |value| {
+                                duplicates.insert(&target, quote!(key), value)
+                            })
+                        }
+                    },
+                    KeyValue::Many(pairs) => {
+                        let iter = capacity.add_many(pairs, conditional);
+                        duplicates.insert_pairs(&target, iter, V::EXTEND)
+                    }
+                    KeyValue::Keyed { values, key } => {
+                        let iter = capacity.add_many(keyed_entries(values, key), conditional);
+                        let (key, value) = keyed_key_value::<V>(key);
+                        let insert = duplicates.insert(&target, key, value);
+                        quote! {
+                            for (key, value) in #iter {
+                                #insert
+                            }
+                        }
                     }
-                },
-            }
-        });
-        quote! {{
-            let mut #target = ::std::collections::HashMap::with_capacity(#initial_capacity);
+                    KeyValue::Remove(keys) => {
+                        capacity.add_in_place();
+                        remove_keys(&target, keys)
+                    }
+                    KeyValue::Optional { key, value } => {
+                        capacity.add_in_place();
+                        optional_entry_updates(key, value, |key, value| {
+                            duplicates.insert(&target, key, value)
+                        })
+                    }
+                    KeyValue::Zip { keys, values } => {
+                        let entries = zip.entries(&keys.expr, &values.expr, keys, values);
+                        let iter = capacity.add_many(entries, conditional);
+                        duplicates.insert_pairs(&target, iter, V::EXTEND)
+                    }
+                })
+            })
+            .collect();
+        let prelude = self.duplicates.prelude();
+        let (spreads, initial_capacity) = capacity.into_output();
+        let hasher = self.hasher.binding();
+        let constructor = self.hasher.constructor(
+            self.hash_crate.collection("HashMap"),
+            Some(initial_capacity),
+        );
+        let duplicate_errors = &self.duplicate_errors;
+        let output = quote! {{
+            #duplicate_errors
+            #prelude
+            #hasher
+            #spreads
+            let mut #target = #constructor;
             #(#updates)*
             #target
-        }}
+        }};
+        self.hash_crate.require("HashMap", output)
+    }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
     }
 }
 
-impl<V> ParseRaw for HashMapInput<V>
+impl<V> Parse for HashMapInput<V>
 where
-    ValueExpr<V>: ToTokens,
-    ValueIterExpr<V>: ToTokens,
+    Value<V>: Parse,
+    ValueExpr<V>: Parse,
 {
-    fn parse_raw(input: TokenStream) -> parse::Result<Self> {
-        Ok(HashMapInput(KeyValueSeq::parse_raw(input)?))
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let hash_crate = HashCrate::from_directives(&mut directives)?;
+        let hasher = Hasher::from_directives(&mut directives)?;
+        let duplicates = DuplicatePolicy::for_map(&mut directives)?;
+        let zip = ZipPolicy::from_directives(&mut directives)?;
+        directives.finish()?;
+        let key_values: KeyValueSeq<V> = input.parse()?;
+        let duplicate_errors = if duplicates.rejects_literal_duplicates() {
+            literal_duplicate_errors(key_values.single_keys(), "key")
+        } else {
+            TokenStream::new()
+        };
+        Ok(HashMapInput {
+            hash_crate,
+            hasher,
+            duplicates,
+            zip,
+            key_values,
+            duplicate_errors,
+        })
+    }
+}
+
+impl<V> MakeFallible for HashMapInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.hasher.make_fallible(fallible);
+        self.duplicates.make_fallible(fallible);
+        self.key_values.make_fallible(fallible);
     }
 }
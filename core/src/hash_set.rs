@@ -1,17 +1,43 @@
-use crate::seq::SeqInput;
+use crate::capacity::Capacity;
+use crate::directive::Directives;
+use crate::duplicates::{literal_duplicate_errors, DuplicatePolicy};
+use crate::exclusion::remove_items;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::hasher::Hasher;
+use crate::krate::HashCrate;
+use crate::set_value::{SetSeq, SetValue};
 use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::parse::{self, Parse, ParseStream};
 
-pub struct HashSetInput<V = Verbatim>(SeqInput<V>);
+pub struct HashSetInput<V = Verbatim> {
+    hash_crate: HashCrate,
+    hasher: Hasher,
+    duplicates: DuplicatePolicy,
+    values: SetSeq<V>,
+    duplicate_errors: TokenStream,
+}
 
 impl<V> Parse for HashSetInput<V>
 where
     Value<V>: Parse,
 {
     fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
-        Ok(HashSetInput(input.parse()?))
+        let mut directives: Directives = input.parse()?;
+        let hash_crate = HashCrate::from_directives(&mut directives)?;
+        let hasher = Hasher::from_directives(&mut directives)?;
+        let duplicates = DuplicatePolicy::for_set(&mut directives)?;
+        directives.finish()?;
+        let values: SetSeq<V> = input.parse()?;
+        let duplicate_errors = literal_duplicate_errors(values.single_items(), "item");
+        Ok(HashSetInput {
+            hash_crate,
+            hasher,
+            duplicates,
+            values,
+            duplicate_errors,
+        })
     }
 }
 
@@ -21,32 +47,60 @@ where
     ValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
-        let values = self.0.values();
-        let initial_capacity = if self.0.is_simple() {
-            values.len()
-        } else {
-            // A simple heuristic for the initial capacity. At this point we can guess that
-            // the output length is likely to be greater than the number of values, since
-            // at least one of the values is an iterator. This will reduce the number of
-            // allocations in common cases, while not massively over-allocating when the
-            // collection is small.
-            16.max(values.len().next_power_of_two() * 2)
-        };
         let target = Ident::new("set", Span::call_site());
-        let updates = values.map(|value| match value {
-            Value::One(expr) => quote! {
-                #target.insert(#expr);
-            },
-            Value::Many(expr) => quote! {
-                for item in #expr {
-                    #target.insert(item);
-                }
-            },
-        });
-        quote! {{
-            let mut #target = ::std::collections::HashSet::with_capacity(#initial_capacity);
+        let mut capacity = Capacity::default();
+        let duplicates = &self.duplicates;
+        let updates: Vec<_> = self
+            .values
+            .values()
+            .map(|element| {
+                element.updates(&mut |value, conditional| match value {
+                    SetValue::Insert(Value::One(expr)) => {
+                        let expr = capacity.add_one(expr, conditional);
+                        duplicates.insert_item(&target, expr)
+                    }
+                    SetValue::Insert(Value::Many(expr)) => {
+                        let iter = capacity.add_many(expr, conditional);
+                        let insert = duplicates.insert_item(&target, quote!(item));
+                        quote! {
+                            for item in #iter {
+                                #insert
+                            }
+                        }
+                    }
+                    SetValue::Remove(items) => {
+                        capacity.add_in_place();
+                        remove_items(&target, items)
+                    }
+                })
+            })
+            .collect();
+        let (spreads, initial_capacity) = capacity.into_output();
+        let hasher = self.hasher.binding();
+        let constructor = self.hasher.constructor(
+            self.hash_crate.collection("HashSet"),
+            Some(initial_capacity),
+        );
+        let duplicate_errors = &self.duplicate_errors;
+        let output = quote! {{
+            #duplicate_errors
+            #hasher
+            #spreads
+            let mut #target = #constructor;
             #(#updates)*
             #target
-        }}
+        }};
+        self.hash_crate.require("HashSet", output)
+    }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+}
+
+impl<V> MakeFallible for HashSetInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.hasher.make_fallible(fallible);
+        self.values.make_fallible(fallible);
     }
 }
@@ -0,0 +1,66 @@
+use crate::directive::Directives;
+use crate::fallible::{Fallible, MakeFallible};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::parse;
+use syn::Expr;
+
+/// The hasher of a hashed collection, which is given by an `@hasher(state);` directive.
+///
+/// Without the directive, the collection has the default hasher of its crate, such as
+/// `RandomState`, so that the type of the collection can be inferred. The hasher type
+/// can still be taken from a type annotation with `@hasher(Default::default());`.
+pub(crate) struct Hasher(Option<Expr>);
+
+impl Hasher {
+    /// Takes the hasher from the macro's directives.
+    pub(crate) fn from_directives(directives: &mut Directives) -> parse::Result<Self> {
+        Ok(Hasher(directives.take("hasher")?))
+    }
+
+    /// Generates a statement that evaluates the hasher, if there is one. It must come
+    /// before the elements are evaluated, since the directive comes before them.
+    pub(crate) fn binding(&self) -> TokenStream {
+        match &self.0 {
+            Some(hasher) => {
+                let ident = hasher_ident();
+                quote! {
+                    let #ident = #hasher;
+                }
+            }
+            None => TokenStream::new(),
+        }
+    }
+
+    /// Generates an expression that creates an empty `collection`, which is the path of
+    /// a hashed collection type, using the hasher from `binding`. If there is a
+    /// `capacity`, the collection is created with room for that many items.
+    pub(crate) fn constructor(
+        &self,
+        collection: TokenStream,
+        capacity: Option<TokenStream>,
+    ) -> TokenStream {
+        let hasher = hasher_ident();
+        match (&self.0, capacity) {
+            (None, None) => quote!(#collection::new()),
+            (None, Some(capacity)) => quote!(#collection::with_capacity(#capacity)),
+            (Some(_), None) => quote!(#collection::with_hasher(#hasher)),
+            (Some(_), Some(capacity)) => {
+                quote!(#collection::with_capacity_and_hasher(#capacity, #hasher))
+            }
+        }
+    }
+}
+
+impl MakeFallible for Hasher {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        if let Some(hasher) = &mut self.0 {
+            fallible.expr(hasher);
+        }
+    }
+}
+
+/// The hasher is bound to a variable that must not be visible to the elements
+fn hasher_ident() -> Ident {
+    Ident::new("hasher", Span::mixed_site())
+}
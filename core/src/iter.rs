@@ -21,19 +21,33 @@ where
     ValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
-        let target = Ident::new("it", Span::call_site());
-        let updates = self.0.values().map(|value| match value {
+        chained(&self.0)
+    }
+}
+
+/// Generates an expression for an iterator over all of the values, by chaining together
+/// an iterator for each element. The iterator has an exact length when each spread
+/// does.
+pub(crate) fn chained<V>(values: &SeqInput<V>) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    let target = Ident::new("it", Span::call_site());
+    let updates = values.values().map(|element| {
+        let iter = element.to_iter(&mut |value| match value {
             Value::One(expr) => quote! {
-                let #target = #target.chain(std::iter::once(#expr));
-            },
-            Value::Many(expr) => quote! {
-                let #target = #target.chain(#expr);
+                ::core::iter::once(#expr)
             },
+            Value::Many(expr) => expr.into_token_stream(),
         });
-        quote! {{
-            let #target = ::std::iter::empty();
-            #(#updates)*
-            #target
-        }}
-    }
+        quote! {
+            let #target = #target.chain(#iter);
+        }
+    });
+    quote! {{
+        let #target = ::core::iter::empty();
+        #(#updates)*
+        #target
+    }}
 }
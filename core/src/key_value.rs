@@ -1,95 +1,564 @@
-use crate::value::{Value, ValueExpr};
-use crate::ParseRaw;
-use proc_macro2::{Spacing, TokenStream, TokenTree};
-use quote::TokenStreamExt;
+use crate::duplicates::SingleKey;
+use crate::element::Element;
+use crate::fallible::{branch, break_on_output, collect_results, Fallible, MakeFallible};
+use crate::krate::velcro_private;
+use crate::value::{
+    remove_range_parens, ConvertInto, TryConvertInto, Value, ValueExpr, ValueIterExpr, Verbatim,
+};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
 use std::marker::PhantomData;
-use syn::parse::Parser;
-use syn::parse::{self, Parse};
+use syn::parse::{self, Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{spanned::Spanned, Expr, ExprClosure, ExprParen, Token};
 
-pub struct KeyValue<V> {
-    key: Value<V>,
-    value: ValueExpr<V>,
+/// An entry in a map-like macro input.
+pub enum KeyValue<V> {
+    /// A key and a value, `key: value`, or many keys with the same value, `..keys: value`.
+    Entry { key: Value<V>, value: ValueExpr<V> },
+    /// Many entries, spread from an iterator of `(key, value)` pairs, `..pairs`.
+    Many(KeyValueIterExpr<V>),
+    /// Many entries, zipped from an iterator of keys and an iterator of values,
+    /// `..keys: ..values`.
+    Zip {
+        keys: ValueIterExpr<V>,
+        values: ValueIterExpr<V>,
+    },
+    /// Many entries, spread from an iterator of values, with each key computed by a
+    /// function of its value, `..values => key`.
+    Keyed { values: ValueIterExpr<V>, key: Expr },
+    /// An entry that is only added if the value is `Some`, `key: ?option`.
+    Optional { key: ValueExpr<V>, value: Expr },
+    /// Removes the entry for one or many keys, if they were added by earlier entries,
+    /// `~key` or `~..keys`.
+    Remove(Value<V>),
 }
 
-impl<V> KeyValue<V> {
-    pub fn key(&self) -> &Value<V> {
-        &self.key
+impl<V> MakeFallible for KeyValue<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            KeyValue::Entry { key, value } => {
+                key.make_fallible(fallible);
+                fallible.expr(&mut value.expr);
+            }
+            KeyValue::Many(pairs) => fallible.spread(&mut pairs.expr),
+            KeyValue::Zip { keys, values } => {
+                fallible.spread(&mut keys.expr);
+                fallible.spread(&mut values.expr);
+            }
+            KeyValue::Keyed { values, key } => {
+                fallible.spread(&mut values.expr);
+                fallible.expr(key);
+            }
+            KeyValue::Remove(keys) => keys.make_fallible(fallible),
+            KeyValue::Optional { key, value } => {
+                fallible.expr(&mut key.expr);
+                fallible.expr(value);
+            }
+        }
+    }
+}
+
+pub struct KeyValueIterExpr<V> {
+    pub(crate) expr: Expr,
+    _phantom: PhantomData<V>,
+}
+
+impl ToTokens for KeyValueIterExpr<Verbatim> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let expr = remove_range_parens(&self.expr);
+        expr.to_tokens(tokens);
+    }
+}
+
+impl ToTokens for KeyValueIterExpr<ConvertInto> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let expr = remove_range_parens(&self.expr);
+        let output = quote_spanned! {
+            expr.span() =>
+            ::core::iter::IntoIterator::into_iter(#expr).map(|(key, value)| {
+                (::core::convert::Into::into(key), ::core::convert::Into::into(value))
+            })
+        };
+        tokens.append_all(output);
+    }
+}
+
+impl ToTokens for KeyValueIterExpr<TryConvertInto> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let expr = remove_range_parens(&self.expr);
+        let key = branch(
+            quote_spanned!(expr.span() => ::core::convert::TryFrom::try_from(key)),
+            expr.span(),
+        );
+        let value = branch(
+            quote_spanned!(expr.span() => ::core::convert::TryFrom::try_from(value)),
+            expr.span(),
+        );
+        let results = quote_spanned! {
+            expr.span() =>
+            ::core::iter::IntoIterator::into_iter(#expr).map(|(key, value)| {
+                ::core::result::Result::and_then(#key, |key| {
+                    ::core::result::Result::map(#value, |value| (key, value))
+                })
+            })
+        };
+        let collected = collect_results(results, expr.span());
+        tokens.append_all(break_on_output(collected, expr.span()));
+    }
+}
+
+/// Generates the statements that add a spread entry, `..keys: value`, to a map, using
+/// `insert` to generate the statements that add the value to the map for `key`.
+///
+/// The value is evaluated once, after the keys, and cloned for each key before it is
+/// converted. If the value is a closure, `..keys: |key| value`, it is called with a
+/// reference to each key instead.
+pub(crate) fn spread_entry_updates<V, F>(
+    keys: impl ToTokens,
+    value: &ValueExpr<V>,
+    insert: F,
+) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    F: FnOnce(TokenStream) -> TokenStream,
+{
+    match &value.expr {
+        Expr::Closure(closure) => {
+            let entries = per_key_entries(keys, closure);
+            let insert = insert(per_key_value::<V>(closure));
+            quote! {
+                for (key, value) in #entries {
+                    #insert
+                }
+            }
+        }
+        _ => {
+            // The keys and the shared value must not be visible to the input
+            let keys_ident = Ident::new("keys", Span::mixed_site());
+            let shared = Ident::new("value", Span::mixed_site());
+            // A closure in parentheses is given to every key, but the parentheses would
+            // cause a warning when it is assigned
+            let expr = match &value.expr {
+                Expr::Paren(ExprParen { expr, .. }) => expr,
+                expr => expr,
+            };
+            let cloned =
+                ValueExpr::<V>::new(syn::parse_quote!(::core::clone::Clone::clone(&#shared)));
+            let insert = insert(cloned.into_token_stream());
+            quote! {{
+                let #keys_ident = ::core::iter::IntoIterator::into_iter(#keys);
+                let #shared = #expr;
+                for key in #keys_ident {
+                    #insert
+                }
+            }}
+        }
     }
+}
 
-    pub fn value(&self) -> &ValueExpr<V> {
-        &self.value
+/// Generates an iterator of `(key, value)` pairs, calling `closure` with a reference to
+/// each of the keys. The values are not converted, see `per_key_value`.
+pub(crate) fn per_key_entries(keys: impl ToTokens, closure: &ExprClosure) -> TokenStream {
+    let private = velcro_private();
+    quote! {
+        #private::per_key(#keys, #closure)
     }
 }
 
+/// Generates the conversion of a `value` from `per_key_entries`.
+pub(crate) fn per_key_value<V>(closure: &ExprClosure) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+{
+    converted::<V>("value", closure.span())
+}
+
+/// Generates an iterator of `(key, value)` pairs from a keyed spread, `..values => key`,
+/// calling the `key` function with a reference to each of the values. Neither the keys
+/// nor the values are converted, see `keyed_key_value`.
+pub(crate) fn keyed_entries<V>(values: &ValueIterExpr<V>, key: &Expr) -> TokenStream {
+    let values = remove_range_parens(&values.expr);
+    let private = velcro_private();
+    quote! {
+        #private::keyed(#values, #key)
+    }
+}
+
+/// Generates the conversions of a `key` and `value` from `keyed_entries`.
+pub(crate) fn keyed_key_value<V>(key: &Expr) -> (TokenStream, TokenStream)
+where
+    ValueExpr<V>: ToTokens,
+{
+    (
+        converted::<V>("key", key.span()),
+        converted::<V>("value", key.span()),
+    )
+}
+
+/// Generates the statements that add an optional entry, `key: ?option`, to a map,
+/// using `insert` to generate the statements that add a key and value to the map. The
+/// key is evaluated before the value, whether or not the value is `Some`.
+pub(crate) fn optional_entry_updates<V, F>(
+    key: &ValueExpr<V>,
+    value: &Expr,
+    insert: F,
+) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    F: FnOnce(TokenStream, TokenStream) -> TokenStream,
+{
+    // The key must not be visible to the value expression
+    let key_ident = Ident::new("key", Span::mixed_site());
+    let insert = insert(
+        key_ident.to_token_stream(),
+        converted::<V>("value", value.span()),
+    );
+    quote! {{
+        let #key_ident = #key;
+        if let ::core::option::Option::Some(value) = #value {
+            #insert
+        }
+    }}
+}
+
+/// Generates an iterator of zero or one `(key, value)` pairs for an optional entry,
+/// `key: ?option`.
+pub(crate) fn optional_entry_iter<V>(key: &ValueExpr<V>, value: &Expr) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+{
+    let key_ident = Ident::new("key", Span::mixed_site());
+    let converted = converted::<V>("value", value.span());
+    quote! {{
+        let #key_ident = #key;
+        ::core::option::Option::map(#value, move |value| (#key_ident, #converted))
+    }}
+}
+
+/// Generates the conversion of a variable, which is an item of an iterator.
+fn converted<V>(name: &str, span: Span) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+{
+    let ident = Ident::new(name, span);
+    ValueExpr::<V>::new(syn::parse_quote!(#ident)).into_token_stream()
+}
+
+/// A comma-delimited sequence of `KeyValue`s, used for macros with map-like input.
 pub struct KeyValueSeq<V> {
-    key_values: Vec<KeyValue<V>>,
+    key_values: Punctuated<Element<KeyValue<V>>, Token![,]>,
     _phantom: PhantomData<V>,
 }
 
 impl<V> KeyValueSeq<V> {
-    pub fn key_values(&self) -> impl ExactSizeIterator<Item = &KeyValue<V>> {
+    pub fn key_values(&self) -> impl ExactSizeIterator<Item = &Element<KeyValue<V>>> {
         self.key_values.iter()
     }
+}
 
-    /// Returns true if the sequence contains no spread values
-    pub fn is_simple(&self) -> bool {
-        self.key_values().all(|kv| kv.key().is_simple())
+impl<V> KeyValueSeq<V> {
+    /// Returns the keys of the entries that are always added or removed individually,
+    /// ignoring spreads and any entries under conditions, comprehensions or attributes.
+    pub(crate) fn single_keys(&self) -> impl Iterator<Item = SingleKey<'_>> {
+        self.key_values.iter().filter_map(|element| match element {
+            Element::Value(KeyValue::Entry {
+                key: Value::One(key),
+                ..
+            }) => Some(SingleKey::Added(&key.expr)),
+            Element::Value(KeyValue::Remove(Value::One(key))) => {
+                Some(SingleKey::Removed(&key.expr))
+            }
+            _ => None,
+        })
     }
 }
 
-impl<V> ParseRaw for KeyValueSeq<V>
-where
-    Value<V>: Parse,
-    ValueExpr<V>: Parse,
-{
-    fn parse_raw(tokens: TokenStream) -> parse::Result<KeyValueSeq<V>> {
-        let mut remainder = tokens;
-        let mut key_values = Vec::new();
-        while !remainder.is_empty() {
-            let (key, tokens) = parse_key(remainder)?;
-            let (value, tokens) = parse_value(tokens)?;
-            remainder = tokens;
-            key_values.push(KeyValue { key, value });
+impl<V> MakeFallible for KeyValueSeq<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        for key_value in self.key_values.iter_mut() {
+            key_value.make_fallible(fallible);
         }
-        Ok(KeyValueSeq {
-            key_values,
-            _phantom: PhantomData,
-        })
     }
 }
 
-fn parse_key<V>(tokens: TokenStream) -> parse::Result<(Value<V>, TokenStream)>
+impl<V> Parse for KeyValue<V>
 where
     Value<V>: Parse,
+    ValueExpr<V>: Parse,
 {
-    let mut it = tokens.into_iter();
-    let mut key = TokenStream::new();
-    while let Some(mut tt) = it.next() {
-        if let TokenTree::Punct(p) = &tt {
-            if p.as_char() == ':' {
-                // Stop when we hit a `:` unless it's actually a `::`
-                if p.spacing() == Spacing::Alone {
-                    break;
-                } else {
-                    key.append(tt);
-                    // safe to unwrap because preceding ':' is not alone
-                    tt = it.next().unwrap();
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        if input.peek(Token![~]) {
+            let _: Token![~] = input.parse()?;
+            return Ok(KeyValue::Remove(input.parse()?));
+        }
+        let key: Value<V> = input.parse()?;
+        if input.peek(Token![:]) {
+            let _: Token![:] = input.parse()?;
+            // `key: ?option` is an optional entry. `?` can't start an expression, so
+            // it doesn't collide with the `?` operator in the `try_` macros
+            if input.peek(Token![?]) {
+                let question: Token![?] = input.parse()?;
+                return match key {
+                    Value::One(key) => Ok(KeyValue::Optional {
+                        key,
+                        value: input.parse()?,
+                    }),
+                    Value::Many(_) => Err(syn::Error::new(
+                        question.span,
+                        "a spread of keys can't have an optional value, `?value`",
+                    )),
+                };
+            }
+            // `key: ..value` is a range, but `..keys: ..values` zips two spreads
+            if let Value::Many(keys) = key {
+                if input.peek(Token![..]) && !input.peek(Token![..=]) {
+                    let _: Token![..] = input.parse()?;
+                    return Ok(KeyValue::Zip {
+                        keys,
+                        values: input.parse()?,
+                    });
                 }
+                return Ok(KeyValue::Entry {
+                    key: Value::Many(keys),
+                    value: input.parse()?,
+                });
+            }
+            Ok(KeyValue::Entry {
+                key,
+                value: input.parse()?,
+            })
+        } else if input.peek(Token![=>]) {
+            match key {
+                Value::Many(values) => {
+                    let _: Token![=>] = input.parse()?;
+                    Ok(KeyValue::Keyed {
+                        values,
+                        key: input.parse()?,
+                    })
+                }
+                Value::One(value) => Err(syn::Error::new(
+                    value.expr.span(),
+                    "only a spread (`..`) can be followed by `=> key`",
+                )),
+            }
+        } else {
+            match key {
+                Value::Many(pairs) => Ok(KeyValue::Many(KeyValueIterExpr {
+                    expr: pairs.expr,
+                    _phantom: PhantomData,
+                })),
+                Value::One(key) => Err(syn::Error::new(
+                    key.expr.span(),
+                    "expected `: value` after key; only a spread (`..`) entry may omit the value",
+                )),
             }
         }
-        key.append(tt);
     }
-    Ok((Value::parse.parse2(key)?, it.collect()))
 }
 
-fn parse_value<V>(tokens: TokenStream) -> parse::Result<(ValueExpr<V>, TokenStream)>
+impl<V> Parse for KeyValueSeq<V>
 where
+    Value<V>: Parse,
     ValueExpr<V>: Parse,
 {
-    let mut it = tokens.into_iter();
-    let value: TokenStream = it
-        .by_ref()
-        .take_while(|tt| !matches!(tt, TokenTree::Punct(p) if p.as_char() == ','))
-        .collect();
-    Ok((ValueExpr::parse.parse2(value)?, it.collect()))
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(KeyValueSeq {
+            key_values: input.parse_terminated(Element::parse, Token![,])?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::{ElementAttributed, ElementIf};
+    use std::str::FromStr as _;
+
+    #[test]
+    fn parse_key_and_value_as_entry() {
+        let tokens = TokenStream::from_str("a: b").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 1);
+        assert!(matches!(
+            key_values[0],
+            Element::Value(KeyValue::Entry { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_spread_keys_and_spread_values_as_zip() {
+        let tokens = TokenStream::from_str("..a: ..b, ..c: ..=d, e: ..f").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 3);
+        assert!(matches!(
+            key_values[0],
+            Element::Value(KeyValue::Zip { .. })
+        ));
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Entry { .. })
+        ));
+        assert!(matches!(
+            key_values[2],
+            Element::Value(KeyValue::Entry { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_spread_with_key_function_as_keyed() {
+        let tokens = TokenStream::from_str("..a => |x| x.id, b: c").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 2);
+        assert!(matches!(
+            key_values[0],
+            Element::Value(KeyValue::Keyed { .. })
+        ));
+    }
+
+    #[test]
+    fn single_value_with_key_function_is_error() {
+        let tokens = TokenStream::from_str("a => |x| x.id").unwrap();
+        assert!(syn::parse2::<KeyValueSeq<Verbatim>>(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_tilde_as_remove() {
+        let tokens = TokenStream::from_str("..a, ~b, ~..c").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 3);
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Remove(Value::One(_)))
+        ));
+        assert!(matches!(
+            key_values[2],
+            Element::Value(KeyValue::Remove(Value::Many(_)))
+        ));
+    }
+
+    #[test]
+    fn parse_value_with_question_mark_as_optional() {
+        let tokens = TokenStream::from_str("a: ?b, c?: d").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 2);
+        assert!(matches!(
+            key_values[0],
+            Element::Value(KeyValue::Optional { .. })
+        ));
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Entry {
+                key: Value::One(ValueExpr {
+                    expr: Expr::Try(_),
+                    ..
+                }),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_spread_key_with_optional_value_is_error() {
+        let tokens = TokenStream::from_str("..a: ?b").unwrap();
+        assert!(syn::parse2::<KeyValueSeq<Verbatim>>(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_spread_without_value_as_many() {
+        let tokens = TokenStream::from_str("..a, b: c, ..d").unwrap();
+        let seq = syn::parse2::<KeyValueSeq<Verbatim>>(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 3);
+        assert!(matches!(key_values[0], Element::Value(KeyValue::Many(_))));
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Entry { .. })
+        ));
+        assert!(matches!(key_values[2], Element::Value(KeyValue::Many(_))));
+    }
+
+    #[test]
+    fn parse_entries_containing_colons_and_commas() {
+        let tokens =
+            TokenStream::from_str("Foo::<A, B>::new(): |a, b| a + b, |x: u8| x: c").unwrap();
+        let seq: KeyValueSeq<Verbatim> = syn::parse2(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 2);
+        assert!(matches!(
+            key_values[0],
+            Element::Value(KeyValue::Entry {
+                value: ValueExpr {
+                    expr: Expr::Closure(_),
+                    ..
+                },
+                ..
+            })
+        ));
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Entry {
+                key: Value::One(ValueExpr {
+                    expr: Expr::Closure(_),
+                    ..
+                }),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_condition_as_if_element() {
+        let tokens =
+            TokenStream::from_str("if a => b: c else ..d, if e { f } else { g }: h").unwrap();
+        let seq: KeyValueSeq<Verbatim> = syn::parse2(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 2);
+        assert!(matches!(
+            key_values[0],
+            Element::If(ElementIf { then, otherwise: Some(otherwise), .. })
+                if matches!(**then, Element::Value(KeyValue::Entry { .. }))
+                && matches!(**otherwise, Element::Value(KeyValue::Many(_)))
+        ));
+        assert!(matches!(
+            key_values[1],
+            Element::Value(KeyValue::Entry {
+                key: Value::One(ValueExpr {
+                    expr: Expr::If(_),
+                    ..
+                }),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_attributes_as_attributed_entry() {
+        let tokens = TokenStream::from_str("#[cfg(a)] b: c, #[cfg(d)] ..e").unwrap();
+        let seq: KeyValueSeq<Verbatim> = syn::parse2(tokens).unwrap();
+        let key_values: Vec<_> = seq.key_values().collect();
+        assert_eq!(key_values.len(), 2);
+        assert!(matches!(
+            key_values[0],
+            Element::Attributed(ElementAttributed { element, .. })
+                if matches!(**element, Element::Value(KeyValue::Entry { .. }))
+        ));
+        assert!(matches!(
+            key_values[1],
+            Element::Attributed(ElementAttributed { element, .. })
+                if matches!(**element, Element::Value(KeyValue::Many(_)))
+        ));
+    }
+
+    #[test]
+    fn key_without_value_is_error() {
+        let tokens = TokenStream::from_str("a, b: c").unwrap();
+        assert!(syn::parse2::<KeyValueSeq<Verbatim>>(tokens).is_err());
+    }
 }
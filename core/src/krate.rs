@@ -0,0 +1,61 @@
+use crate::directive::Directives;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::parse;
+
+/// Returns the path of the `alloc` crate, as re-exported by `velcro`, which provides the
+/// allocating collections used by the generated code. It is the same crate as the one
+/// that `std` re-exports, so it works in crates with and without `std`.
+pub(crate) fn alloc_crate() -> TokenStream {
+    let private = velcro_private();
+    quote!(#private::alloc)
+}
+
+/// The crate whose `HashMap` or `HashSet` a macro creates: `std`, or `hashbrown` if the
+/// input starts with the `@hashbrown;` directive. It is chosen by the input rather than
+/// by the features of `velcro`, because features are shared by every crate in a build,
+/// so a crate that enabled one would change the collections of all the others.
+pub(crate) enum HashCrate {
+    Std,
+    Hashbrown,
+}
+
+impl HashCrate {
+    /// Takes the `@hashbrown;` directive from the macro's directives.
+    pub(crate) fn from_directives(directives: &mut Directives) -> parse::Result<Self> {
+        Ok(if directives.take_flag("hashbrown")? {
+            HashCrate::Hashbrown
+        } else {
+            HashCrate::Std
+        })
+    }
+
+    /// Returns the path of a hashed collection, `HashMap` or `HashSet`, as re-exported
+    /// by `velcro`.
+    pub(crate) fn collection(&self, collection: &str) -> TokenStream {
+        let private = velcro_private();
+        let collection = Ident::new(collection, Span::call_site());
+        match self {
+            HashCrate::Std => quote!(#private::#collection),
+            HashCrate::Hashbrown => quote!(#private::hashbrown::#collection),
+        }
+    }
+
+    /// Wraps the output of a macro that creates a hashed collection, so that it is
+    /// replaced by a compile error if the feature of `velcro` that provides the
+    /// collection is not enabled. The features are checked by `velcro` rather than
+    /// here, since they are the features of that crate.
+    pub(crate) fn require(&self, collection: &str, output: TokenStream) -> TokenStream {
+        let private = velcro_private();
+        match self {
+            HashCrate::Std => quote!(#private::require_std!(#collection, #output)),
+            HashCrate::Hashbrown => quote!(#private::require_hashbrown!(#collection, #output)),
+        }
+    }
+}
+
+/// Returns the path of the module of the `velcro` crate that holds the items used by the
+/// generated code, such as the traits behind the `?` operator in the `try_` macros.
+pub(crate) fn velcro_private() -> TokenStream {
+    quote!(::velcro::__private)
+}
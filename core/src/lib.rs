@@ -1,44 +1,49 @@
+mod array;
+mod binary_heap;
 mod btree_map;
 mod btree_set;
+mod bytes;
+mod capacity;
+mod counter;
+mod directive;
+mod duplicates;
+mod element;
+mod exclusion;
+mod fallible;
 mod hash_map;
 mod hash_set;
+mod hasher;
 mod iter;
 mod key_value;
+mod krate;
 mod linked_list;
 mod map_iter;
+mod multimap;
 mod seq;
+mod set_value;
+mod slice;
+mod string;
 mod value;
+mod vec_deque;
 mod vector;
+mod zip;
 
 pub use crate::{
+    array::ArrayInput,
+    binary_heap::BinaryHeapInput,
     btree_map::BTreeMapInput,
     btree_set::BTreeSetInput,
+    bytes::BytesInput,
+    counter::{BTreeCounterInput, CounterInput},
     hash_map::HashMapInput,
     hash_set::HashSetInput,
     iter::IterInput,
     linked_list::LinkedListInput,
     map_iter::MapIterInput,
-    value::{ConvertInto, Verbatim},
+    multimap::{BTreeMapVecInput, HashMapVecInput},
+    slice::{ArcSliceInput, BoxedSliceInput, RcSliceInput},
+    string::StringInput,
+    value::{Conversion, ConvertInto, TryConvertInto, Verbatim},
+    vec_deque::VecDequeInput,
     vector::VecInput,
 };
-use proc_macro2::TokenStream;
-use syn::parse::Result;
-
-/// Trait to be implemented for parsing syntax that would be rejected by syn::Parse
-pub trait ParseRaw: Sized {
-    fn parse_raw(input: TokenStream) -> Result<Self>;
-}
-
-/// Used in a similar way to `syn::parse_macro_input` macro, but for types that implement
-/// `ParseRaw` instead of `syn::parse::Parse`.
-#[macro_export]
-macro_rules! parse_raw_macro_input {
-    ($tokenstream: ident as $ty: ty) => {
-        match <$ty as $crate::ParseRaw>::parse_raw($tokenstream.into()) {
-            Ok(data) => data,
-            Err(err) => {
-                return ::proc_macro::TokenStream::from(err.to_compile_error());
-            }
-        }
-    };
-}
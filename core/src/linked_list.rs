@@ -1,3 +1,5 @@
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::alloc_crate;
 use crate::seq::SeqInput;
 use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
 use proc_macro2::{Ident, Span, TokenStream};
@@ -21,22 +23,35 @@ where
     ValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
         let values = self.0.values();
         let target = Ident::new("list", Span::call_site());
-        let updates = values.map(|value| match value {
-            Value::One(expr) => quote! {
-                #target.push_back(#expr);
-            },
-            Value::Many(expr) => quote! {
-                for item in #expr {
-                    #target.push_back(item);
-                }
-            },
+        let updates = values.map(|element| {
+            element.updates(&mut |value, _| match value {
+                Value::One(expr) => quote! {
+                    #target.push_back(#expr);
+                },
+                Value::Many(expr) => quote! {
+                    for item in #expr {
+                        #target.push_back(item);
+                    }
+                },
+            })
         });
         quote! {{
-            let mut #target = ::std::collections::LinkedList::new();
+            let mut #target = #alloc::collections::LinkedList::new();
             #(#updates)*
             #target
         }}
     }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+}
+
+impl<V> MakeFallible for LinkedListInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.0.make_fallible(fallible);
+    }
 }
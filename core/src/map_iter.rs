@@ -1,19 +1,34 @@
-use crate::key_value::KeyValueSeq;
+use crate::directive::Directives;
+use crate::key_value::{
+    keyed_entries, keyed_key_value, optional_entry_iter, per_key_entries, per_key_value, KeyValue,
+    KeyValueIterExpr, KeyValueSeq,
+};
 use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
-use crate::ParseRaw;
+use crate::zip::ZipPolicy;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::parse;
+use syn::parse::{self, Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::Expr;
 
-pub struct MapIterInput<V = Verbatim>(KeyValueSeq<V>);
+pub struct MapIterInput<V = Verbatim> {
+    zip: ZipPolicy,
+    key_values: KeyValueSeq<V>,
+}
 
-impl<V> ParseRaw for MapIterInput<V>
+impl<V> Parse for MapIterInput<V>
 where
-    ValueExpr<V>: ToTokens,
-    ValueIterExpr<V>: ToTokens,
+    Value<V>: Parse,
+    ValueExpr<V>: Parse,
 {
-    fn parse_raw(input: TokenStream) -> parse::Result<Self> {
-        Ok(MapIterInput(KeyValueSeq::parse_raw(input)?))
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let zip = ZipPolicy::from_directives(&mut directives)?;
+        directives.finish()?;
+        Ok(MapIterInput {
+            zip,
+            key_values: input.parse()?,
+        })
     }
 }
 
@@ -21,26 +36,55 @@ impl<V> MapIterInput<V>
 where
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
 {
     pub fn into_output(self) -> TokenStream {
         let target = Ident::new("it", Span::call_site());
-        let updates = self.0.key_values().map(|kv| {
-            let key = kv.key();
-            let value = kv.value();
-            match key {
-                Value::One(expr) => quote! {
-                    let #target = #target.chain(::std::iter::once((#expr, #value)));
-                },
-                Value::Many(expr) => quote! {
-                    let #target = #target.chain(
-                        ::std::iter::IntoIterator::into_iter(#expr)
-                            .zip(::std::iter::repeat(#value))
-                    );
+        let updates = self.key_values.key_values().map(|element| {
+            let iter = element.to_iter(&mut |kv| match kv {
+                KeyValue::Entry { key, value } => match key {
+                    Value::One(expr) => quote! {
+                        ::core::iter::once((#expr, #value))
+                    },
+                    Value::Many(expr) => match &value.expr {
+                        Expr::Closure(closure) => {
+                            let entries = per_key_entries(expr, closure);
+                            let value = per_key_value::<V>(closure);
+                            quote! {
+                                ::core::iter::Iterator::map(#entries, |(key, value)| (key, #value))
+                            }
+                        }
+                        _ => quote! {
+                            ::core::iter::Iterator::zip(
+                                ::core::iter::IntoIterator::into_iter(#expr),
+                                ::core::iter::repeat(#value),
+                            )
+                        },
+                    },
                 },
+                KeyValue::Many(pairs) => pairs.into_token_stream(),
+                KeyValue::Optional { key, value } => optional_entry_iter(key, value),
+                KeyValue::Remove(keys) => {
+                    syn::Error::new(keys.span(), "entries can't be removed from an iterator")
+                        .to_compile_error()
+                }
+                KeyValue::Zip { keys, values } => {
+                    self.zip.entries(&keys.expr, &values.expr, keys, values)
+                }
+                KeyValue::Keyed { values, key } => {
+                    let entries = keyed_entries(values, key);
+                    let (key, value) = keyed_key_value::<V>(key);
+                    quote! {
+                        ::core::iter::Iterator::map(#entries, |(key, value)| (#key, #value))
+                    }
+                }
+            });
+            quote! {
+                let #target = #target.chain(#iter);
             }
         });
         quote! {{
-            let #target = ::std::iter::empty();
+            let #target = ::core::iter::empty();
             #(#updates)*
             #target
         }}
@@ -0,0 +1,220 @@
+use crate::directive::Directives;
+use crate::exclusion::remove_keys;
+use crate::hasher::Hasher;
+use crate::key_value::{
+    keyed_entries, keyed_key_value, optional_entry_updates, spread_entry_updates, KeyValue,
+    KeyValueIterExpr, KeyValueSeq,
+};
+use crate::krate::{alloc_crate, HashCrate};
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
+use crate::zip::ZipPolicy;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+use syn::{Expr, ExprRange, RangeLimits};
+
+/// The input of `hash_map_vec!`, which creates a `HashMap<K, Vec<V>>`.
+pub struct HashMapVecInput<V = Verbatim> {
+    hash_crate: HashCrate,
+    hasher: Hasher,
+    entries: MultiMapEntries<V>,
+}
+
+/// The input of `btree_map_vec!`, which creates a `BTreeMap<K, Vec<V>>`.
+pub struct BTreeMapVecInput<V = Verbatim>(MultiMapEntries<V>);
+
+/// The entries of a multimap, where each entry appends to the list of values for its
+/// key, instead of replacing it.
+struct MultiMapEntries<V> {
+    zip: ZipPolicy,
+    key_values: KeyValueSeq<V>,
+}
+
+impl<V> Parse for HashMapVecInput<V>
+where
+    Value<V>: Parse,
+    ValueExpr<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let hash_crate = HashCrate::from_directives(&mut directives)?;
+        let hasher = Hasher::from_directives(&mut directives)?;
+        let zip = ZipPolicy::from_directives(&mut directives)?;
+        directives.finish()?;
+        Ok(HashMapVecInput {
+            hash_crate,
+            hasher,
+            entries: MultiMapEntries {
+                zip,
+                key_values: input.parse()?,
+            },
+        })
+    }
+}
+
+impl<V> Parse for BTreeMapVecInput<V>
+where
+    Value<V>: Parse,
+    ValueExpr<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let zip = ZipPolicy::from_directives(&mut directives)?;
+        directives.finish()?;
+        Ok(BTreeMapVecInput(MultiMapEntries {
+            zip,
+            key_values: input.parse()?,
+        }))
+    }
+}
+
+impl<V> HashMapVecInput<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        let hasher = self.hasher.binding();
+        let constructor = self
+            .hasher
+            .constructor(self.hash_crate.collection("HashMap"), None);
+        let constructor = quote! {{
+            #hasher
+            #constructor
+        }};
+        self.hash_crate
+            .require("HashMap", self.entries.output(constructor))
+    }
+}
+
+impl<V> BTreeMapVecInput<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        self.0.output(quote! {
+            #alloc::collections::BTreeMap::new()
+        })
+    }
+}
+
+impl<V> MultiMapEntries<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+    KeyValueIterExpr<V>: ToTokens,
+{
+    fn output(&self, constructor: TokenStream) -> TokenStream {
+        let alloc = alloc_crate();
+        let target = Ident::new("map", Span::call_site());
+        let push = |key: TokenStream, value: TokenStream| {
+            quote! {
+                #target.entry(#key).or_insert_with(#alloc::vec::Vec::new).push(#value);
+            }
+        };
+        let updates = self.key_values.key_values().map(|element| {
+            element.updates(&mut |kv, _| match kv {
+                KeyValue::Entry { key, value } => match (key, spread_values(value)) {
+                    (Value::One(key), Some(values)) => {
+                        let values = ValueIterExpr::<V>::new(values.clone());
+                        let list = Ident::new("list", Span::mixed_site());
+                        let extend = if V::EXTEND {
+                            quote! {
+                                #list.extend(#values);
+                            }
+                        } else {
+                            quote! {
+                                for value in #values {
+                                    #list.push(value);
+                                }
+                            }
+                        };
+                        quote! {{
+                            let #list = #target.entry(#key).or_insert_with(#alloc::vec::Vec::new);
+                            #extend
+                        }}
+                    }
+                    (Value::One(key), None) => {
+                        push(key.into_token_stream(), value.into_token_stream())
+                    }
+                    (Value::Many(keys), _) => {
+                        spread_entry_updates(keys, value, |value| push(quote!(key), value))
+                    }
+                },
+                KeyValue::Many(pairs) => {
+                    let push = push(quote!(key), quote!(value));
+                    quote! {
+                        for (key, value) in #pairs {
+                            #push
+                        }
+                    }
+                }
+                KeyValue::Zip { keys, values } => {
+                    let entries = self.zip.entries(&keys.expr, &values.expr, keys, values);
+                    let push = push(quote!(key), quote!(value));
+                    quote! {
+                        for (key, value) in #entries {
+                            #push
+                        }
+                    }
+                }
+                KeyValue::Keyed { values, key } => {
+                    let entries = keyed_entries(values, key);
+                    let (key, value) = keyed_key_value::<V>(key);
+                    let push = push(key, value);
+                    quote! {
+                        for (key, value) in #entries {
+                            #push
+                        }
+                    }
+                }
+                KeyValue::Optional { key, value } => optional_entry_updates(key, value, push),
+                KeyValue::Remove(keys) => remove_keys(&target, keys),
+            })
+        });
+        quote! {{
+            let mut #target = #constructor;
+            #(#updates)*
+            #target
+        }}
+    }
+}
+
+/// Returns the values of an entry that appends many values to a key, `key: ..values`,
+/// which is parsed as a key with a `RangeTo` value.
+fn spread_values<V>(value: &ValueExpr<V>) -> Option<&Expr> {
+    match &value.expr {
+        Expr::Range(ExprRange {
+            start: None,
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(values),
+            ..
+        }) => Some(values),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_value(input: &str) -> ValueExpr<Verbatim> {
+        syn::parse2(TokenStream::from_str(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn range_to_value_is_spread_values() {
+        assert!(spread_values(&parse_value("..a")).is_some());
+        assert!(spread_values(&parse_value("..=a")).is_none());
+        assert!(spread_values(&parse_value("(..a)")).is_none());
+        assert!(spread_values(&parse_value("a..b")).is_none());
+    }
+}
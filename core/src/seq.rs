@@ -1,3 +1,5 @@
+use crate::element::Element;
+use crate::fallible::{Fallible, MakeFallible};
 use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
 use proc_macro2::TokenStream;
 use quote::ToTokens;
@@ -7,42 +9,66 @@ use syn::punctuated::{Pair, Punctuated};
 use syn::Token;
 
 /// A comma-delimited sequence of `Value`s, used for macros with list-like input.
-pub struct SeqInput<V = Verbatim> {
-    values: Punctuated<Value<V>, Token![,]>,
+///
+/// Macros that add their own syntax to each item, such as the `<-` of `vec_deque!`,
+/// can use a different item type, `T`, which wraps a `Value`.
+pub struct SeqInput<V = Verbatim, T = Value<V>> {
+    values: Punctuated<Element<T>, Token![,]>,
     _phantom: PhantomData<V>,
 }
 
-impl<V> Parse for SeqInput<V>
+impl<V, T> Parse for SeqInput<V, T>
 where
-    Value<V>: Parse,
+    T: Parse,
 {
     fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
         Ok(SeqInput {
-            values: input.parse_terminated(Value::parse, Token![,])?,
+            values: input.parse_terminated(Element::parse, Token![,])?,
             _phantom: PhantomData,
         })
     }
 }
 
+impl<V, T> MakeFallible for SeqInput<V, T>
+where
+    T: MakeFallible,
+{
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        for value in self.values.iter_mut() {
+            value.make_fallible(fallible);
+        }
+    }
+}
+
+impl<V, T> SeqInput<V, T> {
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &Element<T>> {
+        self.values.iter()
+    }
+}
+
 impl<V> SeqInput<V>
 where
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
 {
-    /// Returns true if the sequence contains no spread values
+    /// Returns true if the sequence contains only single values
     pub fn is_simple(&self) -> bool {
-        self.values.iter().all(Value::is_simple)
-    }
-
-    pub fn values(&self) -> impl ExactSizeIterator<Item = &Value<V>> {
-        self.values.iter()
+        self.values
+            .iter()
+            .all(|element| matches!(element, Element::Value(value) if value.is_simple()))
     }
 
+    /// Generates the values as a comma-delimited list of expressions. This must only
+    /// be used if the sequence `is_simple`.
     pub fn simple_output(self) -> TokenStream {
         self.values
             .into_pairs()
             .map(Pair::into_tuple)
-            .map(|(value, delim)| Pair::new(value.into_token_stream(), delim).into_token_stream())
+            .map(|(element, delim)| match element {
+                Element::Value(value) => Pair::new(value.into_token_stream(), delim),
+                _ => unreachable!("simple_output used with elements that are not values"),
+            })
+            .map(Pair::into_token_stream)
             .collect()
     }
 }
@@ -50,6 +76,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::element::{Clause, ElementAttributed, ElementFor, ElementIf};
     use std::str::FromStr as _;
     use syn::Expr;
 
@@ -67,7 +94,9 @@ mod tests {
         let expected_expr: Expr = syn::parse2(tokens).unwrap();
         let values: Vec<_> = seq.values().collect();
         assert_eq!(values.len(), 1);
-        assert!(matches!(values[0], Value::One(ValueExpr { expr, .. }) if *expr == expected_expr));
+        assert!(
+            matches!(values[0], Element::Value(Value::One(ValueExpr { expr, .. })) if *expr == expected_expr)
+        );
     }
 
     #[test]
@@ -82,10 +111,19 @@ mod tests {
         let values: Vec<_> = seq.values().collect();
         assert_eq!(values.len(), 1);
         assert!(
-            matches!(values[0], Value::Many(ValueIterExpr { expr, .. }) if *expr == expected_expr)
+            matches!(values[0], Element::Value(Value::Many(ValueIterExpr { expr, .. })) if *expr == expected_expr)
         );
     }
 
+    #[test]
+    fn parse_optional_value_as_many() {
+        let tokens = TokenStream::from_str("a, ?b").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        let values: Vec<_> = seq.values().collect();
+        assert!(matches!(values[1], Element::Value(Value::Many(_))));
+        assert!(!seq.is_simple());
+    }
+
     #[test]
     fn empty_input_is_simple() {
         let tokens = TokenStream::from_str("").unwrap();
@@ -106,4 +144,79 @@ mod tests {
         let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
         assert!(!seq.is_simple());
     }
+
+    #[test]
+    fn parse_condition_as_if_element() {
+        let tokens = TokenStream::from_str("a, if b => c, if d => ..e else f").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        let values: Vec<_> = seq.values().collect();
+        assert_eq!(values.len(), 3);
+        assert!(matches!(
+            values[1],
+            Element::If(ElementIf { then, otherwise: None, .. })
+                if matches!(**then, Element::Value(Value::One(_)))
+        ));
+        assert!(matches!(
+            values[2],
+            Element::If(ElementIf { then, otherwise: Some(otherwise), .. })
+                if matches!(**then, Element::Value(Value::Many(_)))
+                && matches!(**otherwise, Element::Value(Value::One(_)))
+        ));
+        assert!(!seq.is_simple());
+    }
+
+    #[test]
+    fn parse_comprehension_as_for_element() {
+        let tokens = TokenStream::from_str("for a in b for (c, d) in e if f => ..g").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        let values: Vec<_> = seq.values().collect();
+        assert_eq!(values.len(), 1);
+        assert!(matches!(
+            values[0],
+            Element::For(ElementFor { clauses, body })
+                if matches!(clauses[..], [Clause::For { .. }, Clause::For { .. }, Clause::If(_)])
+                && matches!(**body, Element::Value(Value::Many(_)))
+        ));
+    }
+
+    #[test]
+    fn comprehension_without_for_clause_is_error() {
+        let tokens = TokenStream::from_str("if a for b in c => d").unwrap();
+        let parse_result: Result<SeqInput<Verbatim>, _> = syn::parse2(tokens);
+        assert!(parse_result.is_err());
+    }
+
+    #[test]
+    fn parse_for_expression_as_one_value() {
+        let tokens = TokenStream::from_str("for a in b { c }").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        assert!(seq.is_simple());
+    }
+
+    #[test]
+    fn parse_if_expression_as_one_value() {
+        let tokens = TokenStream::from_str("if a { b } else { c }").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        assert!(seq.is_simple());
+    }
+
+    #[test]
+    fn parse_attributes_as_attributed_element() {
+        let tokens =
+            TokenStream::from_str("a, #[cfg(b)] #[allow(c)] ..d, #[cfg(e)] if f => g").unwrap();
+        let seq: SeqInput<Verbatim> = syn::parse2(tokens).unwrap();
+        let values: Vec<_> = seq.values().collect();
+        assert_eq!(values.len(), 3);
+        assert!(matches!(
+            values[1],
+            Element::Attributed(ElementAttributed { attrs, element })
+                if attrs.len() == 2 && matches!(**element, Element::Value(Value::Many(_)))
+        ));
+        assert!(matches!(
+            values[2],
+            Element::Attributed(ElementAttributed { element, .. })
+                if matches!(**element, Element::If(_))
+        ));
+        assert!(!seq.is_simple());
+    }
 }
@@ -0,0 +1,124 @@
+use crate::duplicates::SingleKey;
+use crate::element::Element;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::value::{Value, ValueExpr, Verbatim};
+use std::marker::PhantomData;
+use syn::parse::{self, Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+/// An item in a set-like macro input.
+pub enum SetValue<V> {
+    /// One or many items to add to the set, `item` or `..items`.
+    Insert(Value<V>),
+    /// One or many items to remove from the set, if they were added by earlier items,
+    /// `~item` or `~..items`.
+    Remove(Value<V>),
+}
+
+impl<V> Parse for SetValue<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        // `~` is used rather than `!`, so that an item can be negated with `!`
+        if input.peek(Token![~]) {
+            let _: Token![~] = input.parse()?;
+            Ok(SetValue::Remove(input.parse()?))
+        } else {
+            Ok(SetValue::Insert(input.parse()?))
+        }
+    }
+}
+
+impl<V> MakeFallible for SetValue<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            SetValue::Insert(value) | SetValue::Remove(value) => value.make_fallible(fallible),
+        }
+    }
+}
+
+/// A comma-delimited sequence of `SetValue`s, used for macros with set-like input.
+pub struct SetSeq<V = Verbatim> {
+    values: Punctuated<Element<SetValue<V>>, Token![,]>,
+    _phantom: PhantomData<V>,
+}
+
+impl<V> SetSeq<V> {
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &Element<SetValue<V>>> {
+        self.values.iter()
+    }
+
+    /// Returns the items that are always added or removed individually, ignoring
+    /// spreads and any items under conditions, comprehensions or attributes.
+    pub(crate) fn single_items(&self) -> impl Iterator<Item = SingleKey<'_>> {
+        self.values.iter().filter_map(|element| match element {
+            Element::Value(SetValue::Insert(Value::One(ValueExpr { expr, .. }))) => {
+                Some(SingleKey::Added(expr))
+            }
+            Element::Value(SetValue::Remove(Value::One(ValueExpr { expr, .. }))) => {
+                Some(SingleKey::Removed(expr))
+            }
+            _ => None,
+        })
+    }
+}
+
+impl<V> Parse for SetSeq<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(SetSeq {
+            values: input.parse_terminated(Element::parse, Token![,])?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<V> MakeFallible for SetSeq<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        for value in self.values.iter_mut() {
+            value.make_fallible(fallible);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::TokenStream;
+    use std::str::FromStr as _;
+
+    #[test]
+    fn parse_tilde_as_remove() {
+        let tokens = TokenStream::from_str("a, ~b, ..c, ~..d").unwrap();
+        let seq: SetSeq<Verbatim> = syn::parse2(tokens).unwrap();
+        let values: Vec<_> = seq.values().collect();
+        assert_eq!(values.len(), 4);
+        assert!(matches!(
+            values[0],
+            Element::Value(SetValue::Insert(Value::One(_)))
+        ));
+        assert!(matches!(
+            values[1],
+            Element::Value(SetValue::Remove(Value::One(_)))
+        ));
+        assert!(matches!(
+            values[2],
+            Element::Value(SetValue::Insert(Value::Many(_)))
+        ));
+        assert!(matches!(
+            values[3],
+            Element::Value(SetValue::Remove(Value::Many(_)))
+        ));
+    }
+
+    #[test]
+    fn spread_items_are_not_single_items() {
+        let tokens = TokenStream::from_str("a, ~a, ..b, ~..c").unwrap();
+        let seq: SetSeq<Verbatim> = syn::parse2(tokens).unwrap();
+        assert_eq!(seq.single_items().count(), 2);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::iter::chained;
+use crate::krate::alloc_crate;
+use crate::seq::SeqInput;
+use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+
+/// The input of `boxed_slice!`, which creates a `Box<[T]>`.
+pub struct BoxedSliceInput<V = Verbatim>(SeqInput<V>);
+
+/// The input of `rc_slice!`, which creates an `Rc<[T]>`.
+pub struct RcSliceInput<V = Verbatim>(SeqInput<V>);
+
+/// The input of `arc_slice!`, which creates an `Arc<[T]>`.
+pub struct ArcSliceInput<V = Verbatim>(SeqInput<V>);
+
+impl<V> Parse for BoxedSliceInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(BoxedSliceInput(input.parse()?))
+    }
+}
+
+impl<V> BoxedSliceInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        boxed_slice_output(self.0)
+    }
+}
+
+impl<V> Parse for RcSliceInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(RcSliceInput(input.parse()?))
+    }
+}
+
+impl<V> RcSliceInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        slice_output(quote!(rc::Rc), self.0)
+    }
+}
+
+impl<V> Parse for ArcSliceInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(ArcSliceInput(input.parse()?))
+    }
+}
+
+impl<V> ArcSliceInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        slice_output(quote!(sync::Arc), self.0)
+    }
+}
+
+/// Generates a boxed slice, which is allocated once, at its final size, if the size
+/// hint of the values is exact.
+fn boxed_slice_output<V>(values: SeqInput<V>) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    if values.is_simple() {
+        return slice_output(quote!(boxed::Box), values);
+    }
+    // `Box::from_iter` would allocate room for at least four values and then shrink it
+    let alloc = alloc_crate();
+    let iter = chained(&values);
+    let vec = Ident::new("vec", Span::mixed_site());
+    quote! {{
+        let iter = #iter;
+        let mut #vec = #alloc::vec::Vec::with_capacity(::core::iter::Iterator::size_hint(&iter).0);
+        for item in iter {
+            #vec.push(item);
+        }
+        #alloc::vec::Vec::into_boxed_slice(#vec)
+    }}
+}
+
+/// Generates a slice in a smart pointer. Single values are converted from an array.
+/// Otherwise the values are collected from a chained iterator, which `std` only
+/// allocates once if it knows the length to be exact, as it does for arrays, `Vec`s,
+/// ranges and slice iterators, but not for other collections or conditional elements.
+/// Otherwise the values are collected into a `Vec` first. The `pointer` is the path of
+/// the pointer type within `std` or `alloc`, such as `rc::Rc`.
+fn slice_output<V>(pointer: TokenStream, values: SeqInput<V>) -> TokenStream
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    let alloc = alloc_crate();
+    let pointer = quote!(#alloc::#pointer);
+    if values.is_simple() {
+        let values = values.simple_output();
+        quote! {
+            <#pointer<[_]> as ::core::convert::From<_>>::from([#values])
+        }
+    } else {
+        let iter = chained(&values);
+        quote! {
+            <#pointer<[_]> as ::core::iter::FromIterator<_>>::from_iter(#iter)
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::capacity::Capacity;
+use crate::directive::Directives;
+use crate::krate::{alloc_crate, velcro_private};
+use crate::seq::SeqInput;
+use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+use syn::{Expr, ExprLit, Lit};
+
+/// The input of `string!`, which concatenates `char`s and strings into a `String`.
+pub struct StringInput<V = Verbatim> {
+    sep: Option<Expr>,
+    values: SeqInput<V>,
+}
+
+impl<V> Parse for StringInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut directives: Directives = input.parse()?;
+        let sep = directives.take("sep")?;
+        directives.finish()?;
+        Ok(StringInput {
+            sep,
+            values: input.parse()?,
+        })
+    }
+}
+
+impl<V> StringInput<V>
+where
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        let private = velcro_private();
+        let target = Ident::new("string", Span::call_site());
+        let sep = Ident::new("sep", Span::mixed_site());
+        let mut capacity = Capacity::default();
+        let mut literal_len = 0;
+        let updates: Vec<_> = self
+            .values
+            .values()
+            .map(|element| {
+                element.updates(&mut |value, conditional| match value {
+                    Value::One(expr) => {
+                        let literal = literal_len_of(&expr.expr);
+                        if !conditional {
+                            literal_len += literal.unwrap_or(0);
+                        }
+                        let expr = capacity.add_uncounted(expr, conditional);
+                        if literal.is_none() {
+                            capacity.add_size(quote!(#private::str_len(&#expr)));
+                        }
+                        quote! {
+                            ::core::iter::Extend::extend(&mut #target, ::core::iter::once(#expr));
+                        }
+                    }
+                    Value::Many(expr) => {
+                        let iter = capacity.add_many(expr, conditional);
+                        if self.sep.is_some() {
+                            capacity.add_size(quote! {
+                                ::core::iter::Iterator::size_hint(&#iter).0.saturating_sub(1)
+                                    * #private::str_len(&#sep)
+                            });
+                            let first = Ident::new("first", Span::mixed_site());
+                            quote! {
+                                let mut #first = true;
+                                for item in #iter {
+                                    if !#first {
+                                        ::core::iter::Extend::extend(
+                                            &mut #target,
+                                            ::core::iter::once(::core::clone::Clone::clone(&#sep)),
+                                        );
+                                    }
+                                    #first = false;
+                                    ::core::iter::Extend::extend(&mut #target, ::core::iter::once(item));
+                                }
+                            }
+                        } else {
+                            quote! {
+                                ::core::iter::Extend::extend(&mut #target, #iter);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        let sep = self.sep.as_ref().map(|expr| {
+            quote! {
+                let #sep = #expr;
+            }
+        });
+        let (spreads, initial_capacity) = capacity.into_output();
+        quote! {{
+            #sep
+            #spreads
+            let mut #target = #alloc::string::String::with_capacity(#literal_len + #initial_capacity);
+            #(#updates)*
+            #target
+        }}
+    }
+}
+
+/// Returns the length in bytes of a string or `char` literal, or `None` for any other
+/// expression, since its length is not known until it is evaluated.
+fn literal_len_of(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Some(lit.value().len()),
+        Expr::Lit(ExprLit {
+            lit: Lit::Char(lit),
+            ..
+        }) => Some(lit.value().len_utf8()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_expr(input: &str) -> Expr {
+        syn::parse2(TokenStream::from_str(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn literal_lengths_are_known() {
+        assert_eq!(literal_len_of(&parse_expr("\"abc\"")), Some(3));
+        assert_eq!(literal_len_of(&parse_expr("'é'")), Some(2));
+        assert_eq!(literal_len_of(&parse_expr("name")), None);
+    }
+
+    #[test]
+    fn parse_separator_directive() {
+        let tokens = TokenStream::from_str("@sep(\", \"); ..a").unwrap();
+        let input: StringInput = syn::parse2(tokens).unwrap();
+        assert!(input.sep.is_some());
+    }
+}
@@ -1,5 +1,8 @@
+use crate::fallible::{
+    branch, break_on_error, break_on_output, collect_results, Fallible, MakeFallible,
+};
 use proc_macro2::TokenStream;
-use quote::{quote_spanned, ToTokens, TokenStreamExt};
+use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
 use std::marker::PhantomData;
 use syn::parse::{self, Parse, ParseStream};
 use syn::{spanned::Spanned, Expr, ExprParen, Token};
@@ -7,8 +10,8 @@ use syn::{spanned::Spanned, Expr, ExprParen, Token};
 /// An input to a velcro macro, which can be an expression representing one value or
 /// an expression preceded by `..`, representing many values.
 ///
-/// The type parameter is a marker to control the code generation. It can be either
-/// `Verbatim` or `ConvertInto`.
+/// The type parameter is a marker to control the code generation. It can be
+/// `Verbatim`, `ConvertInto` or `TryConvertInto`.
 pub enum Value<V> {
     One(ValueExpr<V>),
     Many(ValueIterExpr<V>),
@@ -29,12 +32,29 @@ where
         if input.peek(Token![..]) {
             let _: Token![..] = input.parse()?;
             Ok(Value::Many(input.parse()?))
+        } else if input.peek(Token![?]) {
+            // An optional value, `?option`, is a spread of zero or one values
+            let _: Token![?] = input.parse()?;
+            let option: Expr = input.parse()?;
+            Ok(Value::Many(ValueIterExpr {
+                expr: syn::parse_quote_spanned!(option.span() => ::core::option::Option::into_iter(#option)),
+                _phantom: PhantomData,
+            }))
         } else {
             Ok(Value::One(input.parse()?))
         }
     }
 }
 
+impl<V> MakeFallible for Value<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            Value::One(value) => fallible.expr(&mut value.expr),
+            Value::Many(values) => fallible.spread(&mut values.expr),
+        }
+    }
+}
+
 impl<V> ToTokens for Value<V>
 where
     ValueExpr<V>: ToTokens,
@@ -62,11 +82,45 @@ impl<V> Parse for ValueExpr<V> {
     }
 }
 
+impl<V> ValueExpr<V> {
+    /// Creates a value for an expression that is generated by a macro, rather than
+    /// parsed from its input.
+    pub(crate) fn new(expr: Expr) -> Self {
+        ValueExpr {
+            expr,
+            _phantom: PhantomData,
+        }
+    }
+}
+
 pub struct ValueIterExpr<V> {
     pub(crate) expr: Expr,
     _phantom: PhantomData<V>,
 }
 
+impl<V> ValueIterExpr<V> {
+    /// Creates a spread for an expression that is generated by a macro, or that was not
+    /// parsed as a spread.
+    pub(crate) fn new(expr: Expr) -> Self {
+        ValueIterExpr {
+            expr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Takes the first `len` items of the iterator. Any conversion is applied to the
+    /// items after they are taken, so the iterator may be infinite.
+    pub(crate) fn take(&self, len: &Expr) -> Self {
+        let expr = remove_range_parens(&self.expr);
+        ValueIterExpr {
+            expr: Expr::Verbatim(quote! {
+                ::core::iter::Iterator::take(::core::iter::IntoIterator::into_iter(#expr), #len)
+            }),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<V> Parse for ValueIterExpr<V> {
     fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
         Ok(ValueIterExpr {
@@ -76,14 +130,40 @@ impl<V> Parse for ValueIterExpr<V> {
     }
 }
 
+/// Implemented by the marker types, which control how values are converted.
+pub trait Conversion {
+    /// True if the items of a spread can be added to a collection with `Extend`.
+    /// Converted items are added one at a time instead, because their type can't be
+    /// inferred when a collection also implements `Extend` for references to its items.
+    const EXTEND: bool;
+}
+
 /// A marker type indicating that input variables should be passed to the result
 /// collection as-is.
 pub struct Verbatim;
 
+impl Conversion for Verbatim {
+    const EXTEND: bool = true;
+}
+
 /// A marker type indicating that input variables need to be converted into the
 /// item (or key) type of the collection
 pub struct ConvertInto;
 
+impl Conversion for ConvertInto {
+    const EXTEND: bool = false;
+}
+
+/// A marker type indicating that input variables need to be converted into the
+/// item (or key) type of the collection with `TryFrom`. Conversion errors are converted
+/// into the macro's error type with `From`. This must only be used in the output of a
+/// `try_` macro.
+pub struct TryConvertInto;
+
+impl Conversion for TryConvertInto {
+    const EXTEND: bool = false;
+}
+
 impl ToTokens for ValueExpr<Verbatim> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.expr.to_tokens(tokens);
@@ -95,15 +175,26 @@ impl ToTokens for ValueExpr<ConvertInto> {
         let expr = &self.expr;
         let output = quote_spanned! {
             expr.span() =>
-            std::convert::Into::into(#expr)
+            ::core::convert::Into::into(#expr)
         };
         tokens.append_all(output);
     }
 }
 
+impl ToTokens for ValueExpr<TryConvertInto> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let expr = &self.expr;
+        let result = quote_spanned! {
+            expr.span() =>
+            ::core::convert::TryFrom::try_from(#expr)
+        };
+        tokens.append_all(break_on_error(result, expr.span()));
+    }
+}
+
 // If there is a Range in parentheses, strip the parentheses to avoid compiler warnings.
 // Leave the parentheses for other types of expression.
-fn remove_range_parens(expression: &Expr) -> &Expr {
+pub(crate) fn remove_range_parens(expression: &Expr) -> &Expr {
     match expression {
         Expr::Paren(ExprParen { expr, .. }) if matches!(**expr, Expr::Range(_)) => expr,
         other => other,
@@ -122,12 +213,28 @@ impl ToTokens for ValueIterExpr<ConvertInto> {
         let expr = remove_range_parens(&self.expr);
         let output = quote_spanned! {
             expr.span() =>
-            ::std::iter::IntoIterator::into_iter(#expr).map(::std::convert::Into::into)
+            ::core::iter::IntoIterator::into_iter(#expr).map(::core::convert::Into::into)
         };
         tokens.append_all(output);
     }
 }
 
+impl ToTokens for ValueIterExpr<TryConvertInto> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let expr = remove_range_parens(&self.expr);
+        let result = branch(
+            quote_spanned!(expr.span() => ::core::convert::TryFrom::try_from(item)),
+            expr.span(),
+        );
+        let results = quote_spanned! {
+            expr.span() =>
+            ::core::iter::IntoIterator::into_iter(#expr).map(|item| #result)
+        };
+        let collected = collect_results(results, expr.span());
+        tokens.append_all(break_on_output(collected, expr.span()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
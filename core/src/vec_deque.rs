@@ -0,0 +1,181 @@
+use crate::capacity::Capacity;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::alloc_crate;
+use crate::seq::SeqInput;
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
+use crate::vector::{has_semicolon_separator, VecRepeatInput};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{self, Parse, ParseStream};
+use syn::Token;
+
+#[allow(clippy::large_enum_variant)]
+pub enum VecDequeInput<V = Verbatim> {
+    Seq(VecDequeSeqInput<V>),
+    Repeat(VecRepeatInput<V>),
+}
+
+impl<V> VecDequeInput<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    pub fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        match self {
+            VecDequeInput::Seq(seq) => seq.into_output(),
+            VecDequeInput::Repeat(repeat) => {
+                let len = repeat.len;
+                match repeat.value {
+                    Value::One(expr) => quote! {
+                        #alloc::collections::VecDeque::from(#alloc::vec![#expr; #len])
+                    },
+                    Value::Many(expr) => {
+                        let iter = expr.take(&len);
+                        quote! {
+                            ::core::iter::IntoIterator::into_iter(#iter)
+                                .collect::<#alloc::collections::VecDeque<_>>()
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+}
+
+impl<V> MakeFallible for VecDequeInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            VecDequeInput::Seq(seq) => seq.0.make_fallible(fallible),
+            VecDequeInput::Repeat(repeat) => repeat.make_fallible(fallible),
+        }
+    }
+}
+
+impl<V> Parse for VecDequeInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(if has_semicolon_separator(input) {
+            VecDequeInput::Repeat(input.parse()?)
+        } else {
+            VecDequeInput::Seq(input.parse()?)
+        })
+    }
+}
+
+/// A value in a `vec_deque!` sequence, which may be preceded by `<-` to indicate
+/// that it should be pushed to the front of the deque instead of the back.
+pub struct VecDequeValue<V> {
+    front: bool,
+    value: Value<V>,
+}
+
+impl<V> Parse for VecDequeValue<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let front = input.peek(Token![<-]);
+        if front {
+            let _: Token![<-] = input.parse()?;
+        }
+        Ok(VecDequeValue {
+            front,
+            value: input.parse()?,
+        })
+    }
+}
+
+impl<V> MakeFallible for VecDequeValue<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.value.make_fallible(fallible);
+    }
+}
+
+pub struct VecDequeSeqInput<V>(SeqInput<V, VecDequeValue<V>>);
+
+impl<V> Parse for VecDequeSeqInput<V>
+where
+    Value<V>: Parse,
+{
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        Ok(VecDequeSeqInput(input.parse()?))
+    }
+}
+
+impl<V> VecDequeSeqInput<V>
+where
+    V: Conversion,
+    ValueExpr<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
+{
+    fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
+        let target = Ident::new("deque", Span::call_site());
+        let mut capacity = Capacity::default();
+        let updates: Vec<_> = self
+            .0
+            .values()
+            .map(|element| {
+                element.updates(
+                    &mut |VecDequeValue { front, value }, conditional| match value {
+                        Value::One(expr) => {
+                            let expr = capacity.add_one(expr, conditional);
+                            if *front {
+                                quote! {
+                                    #target.push_front(#expr);
+                                }
+                            } else {
+                                quote! {
+                                    #target.push_back(#expr);
+                                }
+                            }
+                        }
+                        Value::Many(expr) => {
+                            let iter = capacity.add_many(expr, conditional);
+                            let extend = if V::EXTEND {
+                                quote! {
+                                    #target.extend(#iter);
+                                }
+                            } else {
+                                quote! {
+                                    for item in #iter {
+                                        #target.push_back(item);
+                                    }
+                                }
+                            };
+                            if *front {
+                                // Add the items to the back and then rotate them around to the
+                                // front, so that they keep their order.
+                                // `len` must not be visible to the spread, which may be
+                                // evaluated in place
+                                let len = Ident::new("len", Span::mixed_site());
+                                quote! {{
+                                    let #len = #target.len();
+                                    #extend
+                                    #target.rotate_right(#target.len() - #len);
+                                }}
+                            } else {
+                                extend
+                            }
+                        }
+                    },
+                )
+            })
+            .collect();
+        let (spreads, initial_capacity) = capacity.into_output();
+        quote! {{
+            #spreads
+            let mut #target = #alloc::collections::VecDeque::with_capacity(#initial_capacity);
+            #(#updates)*
+            #target
+        }}
+    }
+}
@@ -1,5 +1,8 @@
+use crate::capacity::Capacity;
+use crate::fallible::{Fallible, MakeFallible};
+use crate::krate::alloc_crate;
 use crate::seq::SeqInput;
-use crate::value::{Value, ValueExpr, ValueIterExpr, Verbatim};
+use crate::value::{Conversion, Value, ValueExpr, ValueIterExpr, Verbatim};
 use proc_macro2::{Ident, Span, TokenStream, TokenTree};
 use quote::{quote, ToTokens};
 use syn::parse::{self, Parse, ParseStream};
@@ -14,6 +17,7 @@ pub enum VecInput<V = Verbatim> {
 
 impl<V> VecInput<V>
 where
+    V: Conversion,
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
 {
@@ -23,6 +27,19 @@ where
             VecInput::Repeat(repeat) => repeat.into_output(),
         }
     }
+
+    pub fn into_try_output(self) -> TokenStream {
+        Fallible::output(self, Self::into_output)
+    }
+}
+
+impl<V> MakeFallible for VecInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        match self {
+            VecInput::Seq(seq) => seq.0.make_fallible(fallible),
+            VecInput::Repeat(repeat) => repeat.make_fallible(fallible),
+        }
+    }
 }
 
 impl<V> Parse for VecInput<V>
@@ -39,7 +56,7 @@ where
     }
 }
 
-fn has_semicolon_separator(input: ParseStream) -> bool {
+pub(crate) fn has_semicolon_separator(input: ParseStream) -> bool {
     input
         .step(|cursor| {
             let mut rest = *cursor;
@@ -58,13 +75,14 @@ fn has_semicolon_separator(input: ParseStream) -> bool {
 }
 
 pub struct VecRepeatInput<V> {
-    value: Value<V>,
-    len: Expr,
+    pub(crate) value: Value<V>,
+    pub(crate) len: Expr,
 }
 
 impl<V> VecRepeatInput<V>
 where
     Value<V>: ToTokens,
+    ValueIterExpr<V>: ToTokens,
 {
     fn into_output(self) -> TokenStream {
         if self.value.is_simple() {
@@ -75,22 +93,35 @@ where
     }
 
     fn simple_output(self) -> TokenStream {
+        let alloc = alloc_crate();
         let len = self.len;
         let expr = &self.value;
         quote! {
-            ::std::vec![#expr; #len]
+            #alloc::vec![#expr; #len]
         }
     }
 
     fn splatted_output(self) -> TokenStream {
-        let len = self.len;
-        let expr = &self.value;
-        quote! {
-            std::iter::IntoIterator::into_iter(#expr).take(#len).collect::<::std::vec::Vec<_>>()
+        let alloc = alloc_crate();
+        match &self.value {
+            Value::Many(expr) => {
+                let iter = expr.take(&self.len);
+                quote! {
+                    ::core::iter::IntoIterator::into_iter(#iter).collect::<#alloc::vec::Vec<_>>()
+                }
+            }
+            Value::One(_) => unreachable!("splatted_output used without a spread"),
         }
     }
 }
 
+impl<V> MakeFallible for VecRepeatInput<V> {
+    fn make_fallible(&mut self, fallible: &mut Fallible) {
+        self.value.make_fallible(fallible);
+        fallible.expr(&mut self.len);
+    }
+}
+
 impl<V> Parse for VecRepeatInput<V>
 where
     Value<V>: Parse,
@@ -119,34 +150,52 @@ where
 
 impl<V> VecSeqInput<V>
 where
+    V: Conversion,
     ValueExpr<V>: ToTokens,
     ValueIterExpr<V>: ToTokens,
 {
     fn into_output(self) -> TokenStream {
+        let alloc = alloc_crate();
         if self.0.is_simple() {
             let values = self.0.simple_output();
             quote! {
-                ::std::vec![#values]
+                #alloc::vec![#values]
             }
         } else {
             let target = Ident::new("vec", Span::call_site());
-            let values = self.0.values();
-            // A simple heuristic for the initial capacity. At this point we can guess that
-            // the output length is likely to be greater than the number of values, since
-            // at least one of the values is an iterator. This will reduce the number of
-            // allocations in common cases, while not massively over-allocating when the
-            // collection is small.
-            let initial_capacity = 16.max(values.len().next_power_of_two() * 2);
-            let updates = values.map(|value| match value {
-                Value::One(expr) => quote! {
-                    #target.push(#expr);
-                },
-                Value::Many(expr) => quote! {
-                    #target.extend(#expr);
-                },
-            });
+            let mut capacity = Capacity::default();
+            let updates: Vec<_> = self
+                .0
+                .values()
+                .map(|element| {
+                    element.updates(&mut |value, conditional| match value {
+                        Value::One(expr) => {
+                            let expr = capacity.add_one(expr, conditional);
+                            quote! {
+                                #target.push(#expr);
+                            }
+                        }
+                        Value::Many(expr) => {
+                            let iter = capacity.add_many(expr, conditional);
+                            if V::EXTEND {
+                                quote! {
+                                    #target.extend(#iter);
+                                }
+                            } else {
+                                quote! {
+                                    for item in #iter {
+                                        #target.push(item);
+                                    }
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect();
+            let (spreads, initial_capacity) = capacity.into_output();
             quote! {{
-                let mut #target = ::std::vec::Vec::with_capacity(#initial_capacity);
+                #spreads
+                let mut #target = #alloc::vec::Vec::with_capacity(#initial_capacity);
                 #(#updates)*
                 #target
             }}
@@ -0,0 +1,153 @@
+use crate::directive::Directives;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprArray, ExprParen, ExprReference};
+
+/// How a map macro handles a zipped entry, `..keys: ..values`, when there are not the
+/// same number of keys and values.
+///
+/// If the lengths of both sides are known at compile time, because they are array
+/// literals, a difference is a compile error, unless the policy is `Truncate`.
+#[derive(Clone, Copy)]
+pub(crate) enum ZipPolicy {
+    /// The extra keys or values are ignored, `@zip(truncate)`.
+    Truncate,
+    /// A difference panics. This is the default, `@zip(panic)`.
+    Panic,
+    /// The lengths of both sides must be known at compile time, `@zip(exact)`.
+    Exact,
+}
+
+impl ZipPolicy {
+    /// Takes the policy from the macro's directives.
+    pub(crate) fn from_directives(directives: &mut Directives) -> parse::Result<Self> {
+        match directives.take::<Ident>("zip")? {
+            None => Ok(ZipPolicy::Panic),
+            Some(policy) if policy == "truncate" => Ok(ZipPolicy::Truncate),
+            Some(policy) if policy == "panic" => Ok(ZipPolicy::Panic),
+            Some(policy) if policy == "exact" => Ok(ZipPolicy::Exact),
+            Some(policy) => Err(syn::Error::new(
+                policy.span(),
+                "expected `truncate`, `panic` or `exact`",
+            )),
+        }
+    }
+
+    /// Generates an iterator of `(key, value)` pairs from the keys and values of a zipped
+    /// entry. `keys` and `values` are the parsed expressions, which are checked at compile
+    /// time, and `keys_iter` and `values_iter` are the expressions that generate them.
+    pub(crate) fn entries(
+        self,
+        keys: &Expr,
+        values: &Expr,
+        keys_iter: impl ToTokens,
+        values_iter: impl ToTokens,
+    ) -> TokenStream {
+        let errors = self.length_errors(keys, values);
+        let entries = match self {
+            ZipPolicy::Truncate | ZipPolicy::Exact => quote! {
+                ::core::iter::Iterator::zip(
+                    ::core::iter::IntoIterator::into_iter(#keys_iter),
+                    #values_iter,
+                )
+            },
+            ZipPolicy::Panic => {
+                let keys = Ident::new("keys", Span::mixed_site());
+                let values = Ident::new("values", Span::mixed_site());
+                quote! {{
+                    let mut #keys = ::core::iter::IntoIterator::into_iter(#keys_iter);
+                    let mut #values = ::core::iter::IntoIterator::into_iter(#values_iter);
+                    ::core::iter::from_fn(move || {
+                        match (
+                            ::core::iter::Iterator::next(&mut #keys),
+                            ::core::iter::Iterator::next(&mut #values),
+                        ) {
+                            (::core::option::Option::Some(key), ::core::option::Option::Some(value)) => {
+                                ::core::option::Option::Some((key, value))
+                            }
+                            (::core::option::Option::None, ::core::option::Option::None) => {
+                                ::core::option::Option::None
+                            }
+                            _ => ::core::panic!("zipped map entry has a different number of keys and values"),
+                        }
+                    })
+                }}
+            }
+        };
+        quote! {{
+            #errors
+            #entries
+        }}
+    }
+
+    fn length_errors(self, keys: &Expr, values: &Expr) -> TokenStream {
+        match (self, known_len(keys), known_len(values)) {
+            (ZipPolicy::Truncate, _, _) => TokenStream::new(),
+            (_, Some(keys_len), Some(values_len)) if keys_len != values_len => {
+                let message = format!(
+                    "zipped map entry has {} keys and {} values",
+                    keys_len, values_len
+                );
+                syn::Error::new(values.span(), message).to_compile_error()
+            }
+            (ZipPolicy::Exact, None, _) => length_unknown(keys),
+            (ZipPolicy::Exact, _, None) => length_unknown(values),
+            _ => TokenStream::new(),
+        }
+    }
+}
+
+fn length_unknown(expr: &Expr) -> TokenStream {
+    syn::Error::new(
+        expr.span(),
+        "`@zip(exact)` requires an array literal, with a length that is known at compile time",
+    )
+    .to_compile_error()
+}
+
+/// Returns the number of items in an array literal, `[a, b, c]`, or a reference to one.
+fn known_len(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Array(ExprArray { elems, .. }) => Some(elems.len()),
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Reference(ExprReference { expr, .. }) => {
+            known_len(expr)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn parse_expr(input: &str) -> Expr {
+        syn::parse2(TokenStream::from_str(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn array_literal_has_known_len() {
+        assert_eq!(known_len(&parse_expr("[1, 2, 3]")), Some(3));
+        assert_eq!(known_len(&parse_expr("&[1, 2]")), Some(2));
+        assert_eq!(known_len(&parse_expr("v")), None);
+    }
+
+    #[test]
+    fn different_known_lengths_is_error() {
+        let keys = parse_expr("[1, 2, 3]");
+        let values = parse_expr("[1, 2]");
+        assert!(!ZipPolicy::Panic.length_errors(&keys, &values).is_empty());
+        assert!(!ZipPolicy::Exact.length_errors(&keys, &values).is_empty());
+        assert!(ZipPolicy::Truncate.length_errors(&keys, &values).is_empty());
+    }
+
+    #[test]
+    fn exact_with_unknown_length_is_error() {
+        let keys = parse_expr("[1, 2]");
+        let values = parse_expr("v");
+        assert!(!ZipPolicy::Exact.length_errors(&keys, &values).is_empty());
+        assert!(ZipPolicy::Panic.length_errors(&keys, &values).is_empty());
+    }
+}
@@ -1,8 +1,10 @@
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 use velcro_core::{
-    parse_raw_macro_input, BTreeMapInput, BTreeSetInput, ConvertInto, HashMapInput, HashSetInput,
-    IterInput, LinkedListInput, MapIterInput, VecInput,
+    ArcSliceInput, ArrayInput, BTreeCounterInput, BTreeMapInput, BTreeMapVecInput, BTreeSetInput,
+    BinaryHeapInput, BoxedSliceInput, BytesInput, ConvertInto, CounterInput, HashMapInput,
+    HashMapVecInput, HashSetInput, IterInput, LinkedListInput, MapIterInput, RcSliceInput,
+    StringInput, TryConvertInto, VecDequeInput, VecInput,
 };
 
 #[proc_macro]
@@ -17,6 +19,18 @@ pub fn btree_set(input: TokenStream) -> TokenStream {
     TokenStream::from(output)
 }
 
+#[proc_macro]
+pub fn binary_heap(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn min_binary_heap(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput).into_min_heap_output();
+    TokenStream::from(output)
+}
+
 #[proc_macro]
 pub fn hash_set(input: TokenStream) -> TokenStream {
     let output = parse_macro_input!(input as HashSetInput).into_output();
@@ -29,15 +43,21 @@ pub fn linked_list(input: TokenStream) -> TokenStream {
     TokenStream::from(output)
 }
 
+#[proc_macro]
+pub fn vec_deque(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecDequeInput).into_output();
+    TokenStream::from(output)
+}
+
 #[proc_macro]
 pub fn hash_map(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as HashMapInput).into_output();
+    let output = parse_macro_input!(input as HashMapInput).into_output();
     TokenStream::from(output)
 }
 
 #[proc_macro]
 pub fn btree_map(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as BTreeMapInput).into_output();
+    let output = parse_macro_input!(input as BTreeMapInput).into_output();
     TokenStream::from(output)
 }
 
@@ -49,7 +69,67 @@ pub fn iter(input: TokenStream) -> TokenStream {
 
 #[proc_macro]
 pub fn map_iter(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as MapIterInput).into_output();
+    let output = parse_macro_input!(input as MapIterInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn hash_map_vec(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as HashMapVecInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn btree_map_vec(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeMapVecInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn counter(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as CounterInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn btree_counter(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeCounterInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn string(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as StringInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn array(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as ArrayInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn bytes(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BytesInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn boxed_slice(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BoxedSliceInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn rc_slice(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as RcSliceInput).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn arc_slice(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as ArcSliceInput).into_output();
     TokenStream::from(output)
 }
 
@@ -65,6 +145,18 @@ pub fn btree_set_from(input: TokenStream) -> TokenStream {
     TokenStream::from(output)
 }
 
+#[proc_macro]
+pub fn binary_heap_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn min_binary_heap_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput<ConvertInto>).into_min_heap_output();
+    TokenStream::from(output)
+}
+
 #[proc_macro]
 pub fn hash_set_from(input: TokenStream) -> TokenStream {
     let output = parse_macro_input!(input as HashSetInput<ConvertInto>).into_output();
@@ -77,6 +169,12 @@ pub fn linked_list_from(input: TokenStream) -> TokenStream {
     TokenStream::from(output)
 }
 
+#[proc_macro]
+pub fn vec_deque_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecDequeInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
 #[proc_macro]
 pub fn iter_from(input: TokenStream) -> TokenStream {
     let output = parse_macro_input!(input as IterInput<ConvertInto>).into_output();
@@ -85,18 +183,145 @@ pub fn iter_from(input: TokenStream) -> TokenStream {
 
 #[proc_macro]
 pub fn map_iter_from(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as MapIterInput<ConvertInto>).into_output();
+    let output = parse_macro_input!(input as MapIterInput<ConvertInto>).into_output();
     TokenStream::from(output)
 }
 
 #[proc_macro]
 pub fn hash_map_from(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as HashMapInput<ConvertInto>).into_output();
+    let output = parse_macro_input!(input as HashMapInput<ConvertInto>).into_output();
     TokenStream::from(output)
 }
 
 #[proc_macro]
 pub fn btree_map_from(input: TokenStream) -> TokenStream {
-    let output = parse_raw_macro_input!(input as BTreeMapInput<ConvertInto>).into_output();
+    let output = parse_macro_input!(input as BTreeMapInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn boxed_slice_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BoxedSliceInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn rc_slice_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as RcSliceInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn arc_slice_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as ArcSliceInput<ConvertInto>).into_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_vec(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_btree_set(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeSetInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_binary_heap(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_min_binary_heap(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput).into_try_min_heap_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_hash_set(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as HashSetInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_linked_list(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as LinkedListInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_vec_deque(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecDequeInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_hash_map(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as HashMapInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn try_btree_map(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeMapInput).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn vec_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn btree_set_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeSetInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn binary_heap_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BinaryHeapInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn min_binary_heap_try_from(input: TokenStream) -> TokenStream {
+    let output =
+        parse_macro_input!(input as BinaryHeapInput<TryConvertInto>).into_try_min_heap_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn hash_set_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as HashSetInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn linked_list_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as LinkedListInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn vec_deque_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as VecDequeInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn hash_map_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as HashMapInput<TryConvertInto>).into_try_output();
+    TokenStream::from(output)
+}
+
+#[proc_macro]
+pub fn btree_map_try_from(input: TokenStream) -> TokenStream {
+    let output = parse_macro_input!(input as BTreeMapInput<TryConvertInto>).into_try_output();
     TokenStream::from(output)
 }
@@ -33,6 +33,241 @@
 //! assert_eq!(map[&'.'], "punctuation");
 //! ```
 //!
+//! ## Conditional elements
+//!
+//! In all of the macros, an element can be included only when a condition is true
+//! with `if condition => element`, optionally followed by `else element`. The
+//! element can be a single value, a spread or a map entry.
+//!
+//! ```
+//! use velcro::{hash_map, vec};
+//!
+//! let verbose = true;
+//! let extras = vec![4, 5];
+//! assert_eq!(vec![1, if verbose => 2, if !verbose => 3 else ..extras], vec![1, 2, 4, 5]);
+//!
+//! let tls = false;
+//! let config = hash_map! {
+//!     "host": "localhost",
+//!     if tls => "port": "443" else "port": "80",
+//!     if tls => "cert": "server.pem",
+//! };
+//! assert_eq!(config["port"], "80");
+//! assert!(!config.contains_key("cert"));
+//! ```
+//!
+//! ## Comprehensions
+//!
+//! An element can also be repeated for each item of an iterator with
+//! `for pattern in iterator => element`. Further `for` clauses can be added to
+//! iterate over combinations of items, and `if condition` clauses filter them.
+//!
+//! ```
+//! use velcro::{hash_map, vec};
+//!
+//! let xs = vec![1, 2, 3];
+//! assert_eq!(vec![0, for x in &xs => x * 2, 99], vec![0, 2, 4, 6, 99]);
+//! assert_eq!(
+//!     vec![for x in 0..3 for y in 0..3 if x != y => (x, y)],
+//!     vec![(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1)],
+//! );
+//!
+//! struct User { id: u32, name: &'static str }
+//! let users = vec![User { id: 1, name: "alice" }, User { id: 2, name: "bob" }];
+//! let names = hash_map! { 0: "root", for u in &users => u.id: u.name };
+//! assert_eq!(names[&2], "bob");
+//! ```
+//!
+//! ## Spread map entries
+//!
+//! In the map macros, a spread entry `..keys: value` gives the same value to each of
+//! the keys. The value is evaluated once, after the keys, and is cloned for each key,
+//! so it must implement `Clone`. In the `_from` macros, each clone is then converted.
+//!
+//! If the value is a closure, `..keys: |key| value`, it is called with a reference to
+//! each key to compute that key's value instead. Wrap a closure in parentheses to
+//! give each key a copy of the closure itself.
+//!
+//! ```
+//! use velcro::hash_map;
+//!
+//! let words = vec!["apple", "fig"];
+//! let lengths = hash_map! { ..words.clone(): |word| word.len() };
+//! assert_eq!(lengths["apple"], 5);
+//!
+//! let lists = hash_map! { ..words: Vec::<u32>::new() };
+//! assert!(lists["fig"].is_empty());
+//! ```
+//!
+//! Spreads on both sides of an entry, `..keys: ..values`, zip the keys with the
+//! values. If there are not the same number of each, the macro panics, or it is a
+//! compile error if both are array literals. A directive before the entries chooses
+//! another policy: `@zip(truncate);` ignores the extra keys or values, and
+//! `@zip(exact);` requires both sides to be array literals of the same length.
+//!
+//! ```
+//! use velcro::{btree_map, hash_map};
+//!
+//! let names = vec!["ann", "bob"];
+//! let scores = hash_map! { ..names.clone(): ..vec![90, 75] };
+//! assert_eq!(scores["bob"], 75);
+//!
+//! let ranks = btree_map! { @zip(truncate); ..names: ..1.. };
+//! assert_eq!(ranks["bob"], 2);
+//! ```
+//!
+//! A spread followed by a function, `..values => key`, inserts each value under the
+//! key that the function returns for a reference to it. This builds an index of the
+//! values.
+//!
+//! ```
+//! use velcro::hash_map;
+//!
+//! struct User {
+//!     id: u32,
+//!     name: &'static str,
+//! }
+//!
+//! let users = vec![User { id: 7, name: "ann" }, User { id: 9, name: "bob" }];
+//! let by_id = hash_map! { ..users => |user| user.id };
+//! assert_eq!(by_id[&9].name, "bob");
+//! ```
+//!
+//! ## Optional elements
+//!
+//! An element `?option`, where `option` is an `Option`, adds the value if it is
+//! `Some` and nothing if it is `None`. In the map macros, an entry `key: ?option`
+//! adds the key with the unwrapped value only if the value is `Some`.
+//!
+//! ```
+//! use velcro::{hash_map, vec};
+//!
+//! let proxy: Option<&str> = None;
+//! let agent = Some("velcro");
+//! let headers = hash_map! {
+//!     "host": "example.com",
+//!     "proxy": ?proxy,
+//!     "user-agent": ?agent,
+//! };
+//! assert_eq!(headers.len(), 2);
+//! assert_eq!(headers["user-agent"], "velcro");
+//!
+//! let (two, three): (Option<i32>, Option<i32>) = (Some(2), None);
+//! assert_eq!(vec![1, ?two, ?three, 4], vec![1, 2, 4]);
+//! ```
+//!
+//! ## Removing items
+//!
+//! In the set and map macros, `~item` removes an item, or the entry for a key, that
+//! was added by an earlier element, and `~..items` removes many. Elements are
+//! applied in order, so a later element can add the item again.
+//!
+//! ```
+//! use velcro::{btree_map, hash_set};
+//!
+//! let reserved = vec!['i', 'o'];
+//! let letters = hash_set![..('a'..='z'), ~..reserved, ~'l'];
+//! assert!(letters.contains(&'a'));
+//! assert!(!letters.contains(&'o'));
+//!
+//! let defaults = btree_map! { "debug": true, "color": true };
+//! let config = btree_map! { ..defaults, ~"debug" };
+//! assert_eq!(config.len(), 1);
+//! ```
+//!
+//! ## Fallible literals
+//!
+//! The macros for vectors, sets, maps, `VecDeque`, `LinkedList` and `BinaryHeap` have
+//! a `try_` variant, such as `try_vec!` or `try_hash_map!`, in which the `?` operator
+//! can be used on elements, keys and values. The macro evaluates to a `Result`
+//! containing the collection, or the first error that occurred. The error does not
+//! return from the enclosing function. When `?` is applied to a whole spread, such as
+//! `..results?`, the spread must be an iterator of `Result`s.
+//!
+//! `iter!` and `map_iter!` have no `try_` variants. Their spreads and comprehensions
+//! are only evaluated as the iterator is consumed, after the macro has returned, so
+//! there would be no `Result` for an error to stop. Use a `try_` macro to collect the
+//! values instead, or iterate over `Result`s.
+//!
+//! As with `?` in a function, errors are converted into the error type of the result
+//! with `From`, so the type of the result usually needs to be annotated. `?` can also
+//! be applied to an `Option`, in which case the macro evaluates to an `Option`.
+//!
+//! ```
+//! use velcro::try_vec;
+//!
+//! fn parse_all(first: &str, rest: &[&str]) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+//!     try_vec![first.parse()?, ..rest.iter().map(|s| s.parse::<u32>())?]
+//! }
+//!
+//! assert_eq!(parse_all("1", &["2", "3"]).unwrap(), vec![1, 2, 3]);
+//! assert!(parse_all("1", &["2", "x"]).is_err());
+//!
+//! let (a, b): (Option<u32>, Option<u32>) = (Some(1), None);
+//! let values: Option<Vec<u32>> = try_vec![a?, 2];
+//! assert_eq!(values, Some(vec![1, 2]));
+//! let values: Option<Vec<u32>> = try_vec![a?, b?];
+//! assert_eq!(values, None);
+//! ```
+//!
+//! ## Attributes
+//!
+//! Elements and map entries may have outer attributes. In particular, `#[cfg]` and
+//! `#[cfg_attr]` can be used to leave out elements depending on the build
+//! configuration.
+//!
+//! ```
+//! use velcro::{hash_map, vec};
+//!
+//! let plugins = hash_map! {
+//!     "core": 0,
+//!     #[cfg(not(any()))]
+//!     "enabled": 1,
+//!     #[cfg(any())]
+//!     "disabled": 2,
+//! };
+//! assert_eq!(plugins.len(), 2);
+//! assert!(!plugins.contains_key("disabled"));
+//!
+//! assert_eq!(vec![1, #[cfg(any())] ..[2, 3], 4], vec![1, 4]);
+//! ```
+//!
+//! ## `no_std`
+//!
+//! Velcro can be used in `#![no_std]` crates that have `alloc`, by disabling the
+//! default `std` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! velcro = { version = "0.5", default-features = false }
+//! ```
+//!
+//! The generated code always uses the collections of `alloc`, through `velcro`, so
+//! the crate doesn't need to declare `extern crate alloc;`.
+//!
+//! **`HashMap` and `HashSet` are not in `alloc`.** Without the `std` feature,
+//! `hash_map!`, `hash_set!`, `hash_map_vec!`, `counter!` and their variants are a
+//! compile error. Instead, they can create the `HashMap` and `HashSet` of the
+//! [`hashbrown`](https://docs.rs/hashbrown) crate, by enabling the `hashbrown` feature
+//! and starting the input with the `@hashbrown;` directive:
+//!
+//! ```toml
+//! [dependencies]
+//! velcro = { version = "0.5", default-features = false, features = ["hashbrown"] }
+//! ```
+//!
+//! ```rust,ignore
+//! let map: hashbrown::HashMap<_, _> = velcro::hash_map! { @hashbrown; 'a': 1 };
+//! ```
+//!
+//! The collection is chosen by the directive, rather than by the features, because
+//! Cargo features are shared by every crate in a build, so enabling one in a crate
+//! would change the collections created in all of the others.
+//!
+//! The generated code refers to items in the `velcro` crate by its name, so `velcro`
+//! must be a direct dependency of the crate that uses the macros, and it must not be
+//! renamed.
+//!
 //! ## Contributing
 //!
 //! Contributions are welcome! Check the [Github issue tracker](https://github.com/peterjoel/velcro/issues)
@@ -50,6 +285,8 @@
 //!
 //! For help, questions or to report an issue, please use the [Github issue tracker](https://github.com/peterjoel/velcro/issues).
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
 /// A more flexible vector initialization macro. `velcro::vec!` is a
 /// drop-in replacement for the built-in `std::vec!` macro, but with extra
 /// functionality. In particular, it adds the `..` spread operator, which
@@ -89,6 +326,20 @@
 /// For syntax that is supported by `std::vec!`, `velco::vec!` performs the same,
 /// since it delegates to `std::vec!` wherever the input is compatible. That is,
 /// if you don't use the `..` spread operator, you don't pay for it.
+///
+/// When the `..` operator is used, each spread is first converted into an iterator,
+/// in the order that they appear. The vector is then allocated just once, with a
+/// capacity that is the number of single values plus the lower bounds of the
+/// spreads' [`size_hint`](std::iter::Iterator::size_hint)s. This is exact for
+/// collections, ranges and most other iterators with a known length.
+///
+/// ```rust
+/// use velcro::vec;
+///
+/// let payload = vec![0; 1000];
+/// let packet = vec![1, ..payload, 2];
+/// assert_eq!(packet.capacity(), 1002);
+/// ```
 pub use velcro_macros::vec;
 
 /// Works the same as `vec!` except that values may be of any type that can be
@@ -116,6 +367,74 @@ pub use velcro_macros::vec;
 /// ```
 pub use velcro_macros::vec_from;
 
+/// Works the same as `vec!`, except that the `?` operator may be used in elements
+/// and spreads, and the macro evaluates to a `Result<Vec<T>, E>`.
+///
+/// When an error occurs, no further elements are evaluated and the macro evaluates
+/// to the error, instead of returning it from the enclosing function. Applying `?` to
+/// a whole spread collects an iterator of `Result`s, stopping at the first error.
+/// Errors are converted with `From`, so the type of the result usually needs to be
+/// annotated. See [Fallible literals](crate#fallible-literals).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::num::ParseIntError;
+/// use velcro::try_vec;
+///
+/// let parse = |s: &str| s.parse::<u32>();
+/// let lines = vec!["2", "3"];
+///
+/// let values: Result<Vec<u32>, ParseIntError> =
+///     try_vec![parse("1")?, ..lines.iter().map(|s| parse(s))?, 4];
+/// assert_eq!(values, Ok(vec![1, 2, 3, 4]));
+///
+/// let values: Result<Vec<u32>, ParseIntError> = try_vec![parse("1")?, parse("x")?];
+/// assert!(values.is_err());
+///
+/// let values: Result<Vec<u32>, ParseIntError> = try_vec![parse("7")?; 2];
+/// assert_eq!(values, Ok(vec![7, 7]));
+/// ```
+///
+/// `?` can also be used in the arguments of macros, such as `format!` and `vec!`, whose
+/// arguments are expressions. In the arguments of other macros, it is a compile error,
+/// since it would return from the enclosing function:
+///
+/// ```compile_fail
+/// use std::num::ParseIntError;
+/// use velcro::try_vec;
+///
+/// let parse = |s: &str| s.parse::<u32>();
+/// let values: Result<Vec<bool>, ParseIntError> = try_vec![matches!(parse("1")?, n if n > 1)];
+/// ```
+pub use velcro_macros::try_vec;
+
+/// Works the same as `vec_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<Vec<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_vec!`](crate::try_vec).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::num::TryFromIntError;
+/// use velcro::vec_try_from;
+///
+/// let bytes: Result<Vec<u8>, TryFromIntError> = vec_try_from![1u64, 2i32, ..[3u16, 4]];
+/// assert_eq!(bytes, Ok(vec![1, 2, 3, 4]));
+///
+/// let bytes: Result<Vec<u8>, TryFromIntError> = vec_try_from![1u64, 256u64];
+/// assert!(bytes.is_err());
+///
+/// // The items of a spread are converted after they are taken
+/// let bytes: Result<Vec<u8>, TryFromIntError> = vec_try_from![..(254u32..); 2];
+/// assert_eq!(bytes, Ok(vec![254, 255]));
+/// ```
+pub use velcro_macros::vec_try_from;
+
 /// An initializer for `BTreeSet`, allowing for items to be specified individually
 /// or "spread" using the `..` operator.
 ///
@@ -129,6 +448,26 @@ pub use velcro_macros::vec_from;
 /// assert_eq!(btree_set![..(0..7)], set);
 /// assert_eq!(btree_set![0, 1, ..(2..7)], set);
 ///```
+///
+/// # Duplicate items
+///
+/// An item that is given more than once is only added to the set once. Use the
+/// `@unique;` directive to panic instead.
+///
+/// ```rust,should_panic
+/// use velcro::btree_set;
+///
+/// // panics: duplicate item in set literal: 3
+/// let set = btree_set![@unique; 1, 3, ..(3..5)];
+///```
+///
+/// A literal or path item that is written more than once is a compile error.
+///
+/// ```compile_fail
+/// use velcro::btree_set;
+///
+/// let set = btree_set![1, 2, 1];
+///```
 pub use velcro_macros::btree_set;
 
 /// An initializer for `BTreeSet` that works the same as `btree_set!` except that
@@ -157,6 +496,209 @@ pub use velcro_macros::btree_set;
 ///```
 pub use velcro_macros::btree_set_from;
 
+/// Works the same as `btree_set!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<BTreeSet<T>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use std::num::ParseIntError;
+/// use velcro::{btree_set, try_btree_set};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let set: Result<BTreeSet<u32>, ParseIntError> =
+///     try_btree_set![parse("1")?, ..["2", "1"].iter().map(|s| parse(s))?];
+/// assert_eq!(set, Ok(btree_set![1, 2]));
+///
+/// let set: Result<BTreeSet<u32>, ParseIntError> = try_btree_set![parse("1")?, parse("x")?];
+/// assert!(set.is_err());
+/// ```
+pub use velcro_macros::try_btree_set;
+
+/// Works the same as `btree_set_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<BTreeSet<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_btree_set!`](crate::try_btree_set).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use std::num::TryFromIntError;
+/// use velcro::{btree_set, btree_set_try_from};
+///
+/// let set: Result<BTreeSet<u8>, TryFromIntError> = btree_set_try_from![1u64, ..(2u32..4)];
+/// assert_eq!(set, Ok(btree_set![1, 2, 3]));
+/// ```
+pub use velcro_macros::btree_set_try_from;
+
+/// An initializer for `BinaryHeap`, allowing for items to be specified individually
+/// or "spread" using the `..` operator.
+///
+/// The items are collected into a `Vec`, which is allocated once with a capacity
+/// calculated in the same way as `vec!`, and then converted into a heap.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::binary_heap;
+/// let heap = binary_heap![3, ..(4..7), 0, ..vec![1, 2]];
+///
+/// assert_eq!(heap.into_sorted_vec(), vec![0, 1, 2, 3, 4, 5, 6]);
+///```
+pub use velcro_macros::binary_heap;
+
+/// An initializer for `BinaryHeap` that works the same as `binary_heap!` except that
+/// values can be of any type that can be converted into the collection's item type.
+///
+/// The type of the item must be known at compile time, and usually this means an
+/// explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::collections::BinaryHeap;
+/// use velcro::binary_heap_from;
+///
+/// #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Foo(u64);
+///
+/// impl From<u64> for Foo {
+///     fn from(other: u64) -> Self {
+///         Foo(other)
+///     }
+/// }
+///
+/// let mut foos: BinaryHeap<Foo> = binary_heap_from![1, Foo(3), ..(4..=6), 2];
+/// assert_eq!(foos.pop(), Some(Foo(6)));
+///```
+pub use velcro_macros::binary_heap_from;
+
+/// Works the same as `binary_heap!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<BinaryHeap<T>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BinaryHeap;
+/// use std::num::ParseIntError;
+/// use velcro::try_binary_heap;
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let heap: Result<BinaryHeap<u32>, ParseIntError> = try_binary_heap![parse("1")?, 3, parse("2")?];
+/// assert_eq!(heap.unwrap().pop(), Some(3));
+///
+/// let heap: Result<BinaryHeap<u32>, ParseIntError> = try_binary_heap![parse("1")?, parse("x")?];
+/// assert!(heap.is_err());
+/// ```
+pub use velcro_macros::try_binary_heap;
+
+/// Works the same as `binary_heap_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<BinaryHeap<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_binary_heap!`](crate::try_binary_heap).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BinaryHeap;
+/// use std::num::TryFromIntError;
+/// use velcro::binary_heap_try_from;
+///
+/// let heap: Result<BinaryHeap<u8>, TryFromIntError> = binary_heap_try_from![1u64, ..(2u32..4)];
+/// assert_eq!(heap.unwrap().pop(), Some(3));
+/// ```
+pub use velcro_macros::binary_heap_try_from;
+
+/// An initializer for a `BinaryHeap` that pops its smallest item first. Every item,
+/// including the items of spreads, is wrapped in `std::cmp::Reverse`.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::cmp::Reverse;
+/// use velcro::min_binary_heap;
+/// let mut heap = min_binary_heap![3, ..(4..7), 1, ..vec![2, 0]];
+///
+/// assert_eq!(heap.pop(), Some(Reverse(0)));
+/// assert_eq!(heap.pop(), Some(Reverse(1)));
+/// assert_eq!(heap.into_iter().map(|Reverse(x)| x).max(), Some(6));
+///```
+pub use velcro_macros::min_binary_heap;
+
+/// An initializer for a `BinaryHeap` that pops its smallest item first, and works
+/// the same as `min_binary_heap!` except that values can be of any type that can be
+/// converted into the collection's item type. Each value is converted before it is
+/// wrapped in `std::cmp::Reverse`.
+///
+/// The type of the item must be known at compile time, and usually this means an
+/// explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::cmp::Reverse;
+/// use std::collections::BinaryHeap;
+/// use velcro::min_binary_heap_from;
+///
+/// let mut heap: BinaryHeap<Reverse<u64>> = min_binary_heap_from![3u8, ..(4u32..7), 1u16];
+/// assert_eq!(heap.pop(), Some(Reverse(1)));
+///```
+pub use velcro_macros::min_binary_heap_from;
+
+/// Works the same as `min_binary_heap!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<BinaryHeap<Reverse<T>>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::cmp::Reverse;
+/// use std::collections::BinaryHeap;
+/// use std::num::ParseIntError;
+/// use velcro::try_min_binary_heap;
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let heap: Result<BinaryHeap<Reverse<u32>>, ParseIntError> =
+///     try_min_binary_heap![parse("1")?, 3, parse("2")?];
+/// assert_eq!(heap.unwrap().pop(), Some(Reverse(1)));
+/// ```
+pub use velcro_macros::try_min_binary_heap;
+
+/// Works the same as `min_binary_heap_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<BinaryHeap<Reverse<T>>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_min_binary_heap!`](crate::try_min_binary_heap).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::cmp::Reverse;
+/// use std::collections::BinaryHeap;
+/// use std::num::TryFromIntError;
+/// use velcro::min_binary_heap_try_from;
+///
+/// let heap: Result<BinaryHeap<Reverse<u8>>, TryFromIntError> =
+///     min_binary_heap_try_from![3u64, ..(1u32..3)];
+/// assert_eq!(heap.unwrap().pop(), Some(Reverse(1)));
+/// ```
+pub use velcro_macros::min_binary_heap_try_from;
+
 /// An initializer for `HashSet`, allowing for items to be specified individually
 /// or "spread" using the `..` operator.
 ///
@@ -170,6 +712,44 @@ pub use velcro_macros::btree_set_from;
 /// assert_eq!(hash_set![..(0..7)], set);
 /// assert_eq!(hash_set![0, 1, ..(2..7)], set);
 ///```
+///
+/// # Duplicate items
+///
+/// An item that is given more than once is only added to the set once. Use the
+/// `@unique;` directive to panic instead.
+///
+/// ```rust,should_panic
+/// use velcro::hash_set;
+///
+/// // panics: duplicate item in set literal: 3
+/// let set = hash_set![@unique; 1, 3, ..(3..5)];
+///```
+///
+/// A literal or path item that is written more than once is a compile error.
+///
+/// ```compile_fail
+/// use velcro::hash_set;
+///
+/// let set = hash_set![1, 2, 1];
+///```
+///
+/// # Hashers
+///
+/// The set uses `RandomState` unless a hasher is given with an `@hasher(state);`
+/// directive before the items. Use `@hasher(Default::default());` to create the
+/// hasher type from a type annotation.
+///
+/// ```rust
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::collections::HashSet;
+/// use std::hash::BuildHasherDefault;
+/// use velcro::hash_set;
+///
+/// type MyHashSet<T> = HashSet<T, BuildHasherDefault<DefaultHasher>>;
+///
+/// let set: MyHashSet<_> = hash_set![@hasher(Default::default()); 0, 1, ..(2..7)];
+/// assert!(set.contains(&6));
+///```
 pub use velcro_macros::hash_set;
 
 /// An initializer for `HashSet` that works the same as `hash_set!` except that
@@ -199,6 +779,48 @@ pub use velcro_macros::hash_set;
 ///```
 pub use velcro_macros::hash_set_from;
 
+/// Works the same as `hash_set!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<HashSet<T>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use std::num::ParseIntError;
+/// use velcro::{hash_set, try_hash_set};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let set: Result<HashSet<u32>, ParseIntError> =
+///     try_hash_set![parse("1")?, ..["2", "1"].iter().map(|s| parse(s))?];
+/// assert_eq!(set, Ok(hash_set![1, 2]));
+///
+/// let set: Result<HashSet<u32>, ParseIntError> = try_hash_set![parse("1")?, parse("x")?];
+/// assert!(set.is_err());
+/// ```
+pub use velcro_macros::try_hash_set;
+
+/// Works the same as `hash_set_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<HashSet<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_hash_set!`](crate::try_hash_set).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use std::num::TryFromIntError;
+/// use velcro::{hash_set, hash_set_try_from};
+///
+/// let set: Result<HashSet<u8>, TryFromIntError> = hash_set_try_from![1u64, ..(2u32..4)];
+/// assert_eq!(set, Ok(hash_set![1, 2, 3]));
+/// ```
+pub use velcro_macros::hash_set_try_from;
+
 /// An initializer for `LinkedList`, allowing for items to be specified individually
 /// or "spread" using the `..` operator.
 ///
@@ -236,34 +858,261 @@ pub use velcro_macros::linked_list;
 ///     }
 /// }
 ///
-/// let foos: LinkedList<Foo> = linked_list_from![1, 2, Foo(3), ..(4..=6), 7];
-/// assert_eq!(foos, linked_list![Foo(1), Foo(2), Foo(3), Foo(4), Foo(5), Foo(6), Foo(7)]);
+/// let foos: LinkedList<Foo> = linked_list_from![1, 2, Foo(3), ..(4..=6), 7];
+/// assert_eq!(foos, linked_list![Foo(1), Foo(2), Foo(3), Foo(4), Foo(5), Foo(6), Foo(7)]);
+///```
+pub use velcro_macros::linked_list_from;
+
+/// Works the same as `linked_list!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<LinkedList<T>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::LinkedList;
+/// use std::num::ParseIntError;
+/// use velcro::{linked_list, try_linked_list};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let list: Result<LinkedList<u32>, ParseIntError> = try_linked_list![parse("1")?, 2];
+/// assert_eq!(list, Ok(linked_list![1, 2]));
+///
+/// let list: Result<LinkedList<u32>, ParseIntError> = try_linked_list![parse("1")?, parse("x")?];
+/// assert!(list.is_err());
+/// ```
+pub use velcro_macros::try_linked_list;
+
+/// Works the same as `linked_list_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<LinkedList<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_linked_list!`](crate::try_linked_list).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::LinkedList;
+/// use std::num::TryFromIntError;
+/// use velcro::{linked_list, linked_list_try_from};
+///
+/// let list: Result<LinkedList<u8>, TryFromIntError> = linked_list_try_from![1u64, ..(2u32..4)];
+/// assert_eq!(list, Ok(linked_list![1, 2, 3]));
+/// ```
+pub use velcro_macros::linked_list_try_from;
+
+/// An initializer for `VecDeque`, allowing for items to be specified individually
+/// or "spread" using the `..` operator. Like `vec!`, it also supports the
+/// `[value; len]` syntax.
+///
+/// A value or spread that is preceded by `<-` is pushed to the front of the deque,
+/// instead of the back. Values are added in the order that they appear, so later
+/// values marked with `<-` will be in front of earlier ones. The items of a spread
+/// marked with `<-` keep their order.
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::collections::VecDeque;
+/// use velcro::vec_deque;
+/// let deque: VecDeque<_> = (0..7).into_iter().collect();
+///
+/// assert_eq!(vec_deque![..(0..7)], deque);
+/// assert_eq!(vec_deque![0, 1, ..(2..7)], deque);
+/// assert_eq!(vec_deque![3, 4, ..(5..7), <- 2, <- ..(0..2)], deque);
+///
+/// assert_eq!(vec_deque![3; 5], VecDeque::from(vec![3, 3, 3, 3, 3]));
+/// assert_eq!(vec_deque![..(0..); 7], deque);
+///```
+pub use velcro_macros::vec_deque;
+
+/// An initializer for `VecDeque` that works the same as `vec_deque!` except that
+/// values can be of any type that can be converted into the collection's item
+/// type via an `Into` implementation.
+///
+/// The type of the item must be known at compile time, and usually this means an
+/// explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::collections::VecDeque;
+/// use velcro::{vec_deque, vec_deque_from};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Foo(u64);
+///
+/// impl From<u64> for Foo {
+///     fn from(other: u64) -> Self {
+///         Foo(other)
+///     }
+/// }
+///
+/// let foos: VecDeque<Foo> = vec_deque_from![2, Foo(3), ..(4..=6), <- 1, <- Foo(0)];
+/// assert_eq!(foos, vec_deque![Foo(0), Foo(1), Foo(2), Foo(3), Foo(4), Foo(5), Foo(6)]);
+///```
+pub use velcro_macros::vec_deque_from;
+
+/// Works the same as `vec_deque!`, except that the `?` operator may be used in
+/// elements and spreads, and the macro evaluates to a `Result<VecDeque<T>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::VecDeque;
+/// use std::num::ParseIntError;
+/// use velcro::{try_vec_deque, vec_deque};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let deque: Result<VecDeque<u32>, ParseIntError> = try_vec_deque![parse("1")?, <- parse("0")?];
+/// assert_eq!(deque, Ok(vec_deque![0, 1]));
+///
+/// let deque: Result<VecDeque<u32>, ParseIntError> = try_vec_deque![parse("1")?, parse("x")?];
+/// assert!(deque.is_err());
+/// ```
+pub use velcro_macros::try_vec_deque;
+
+/// Works the same as `vec_deque_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<VecDeque<T>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_vec_deque!`](crate::try_vec_deque).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::VecDeque;
+/// use std::num::TryFromIntError;
+/// use velcro::{vec_deque, vec_deque_try_from};
+///
+/// let deque: Result<VecDeque<u8>, TryFromIntError> = vec_deque_try_from![1u64, <- 0i32];
+/// assert_eq!(deque, Ok(vec_deque![0, 1]));
+/// ```
+pub use velcro_macros::vec_deque_try_from;
+
+/// An initializer for `HashMap`, allowing for entries to be specified individually
+/// or for the same value to be given to multiple keys using the `..` operator.
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// use velcro::hash_map;
+/// let mut map1 = HashMap::new();
+/// map1.insert('a', 0);
+/// map1.insert('b', 1);
+/// map1.insert('c', 1);
+/// map1.insert('d', 1);
+/// map1.insert('e', 1);
+/// map1.insert('f', 2);
+///
+/// let map2 = hash_map! {
+///     'a': 0,
+///     ..('b'..='e'): 1,
+///     'f': 2
+/// };
+///
+/// assert_eq!(map1, map2);
+///```
+///
+/// A spread entry without a value, `..other`, inserts all of the entries from
+/// another map, or from any iterator of `(key, value)` pairs. Entries are inserted
+/// in order, so later entries replace earlier ones with the same key.
+///
+/// ```rust
+/// use velcro::hash_map;
+///
+/// let defaults = hash_map! {
+///     "host": "localhost",
+///     "port": "80",
+/// };
+///
+/// let config = hash_map! {
+///     ..defaults,
+///     "port": "8080",
+///     ..vec![("user", "admin")],
+/// };
+///
+/// assert_eq!(config["host"], "localhost");
+/// assert_eq!(config["port"], "8080");
+/// assert_eq!(config["user"], "admin");
+///```
+///
+/// # Duplicate keys
+///
+/// When a key is given more than once, the last value replaces the earlier ones.
+/// A directive before the entries changes this:
+///
+/// * `@keep_first;` keeps the first value for each key.
+/// * `@unique;` panics if a key is given more than once.
+/// * `@merge(f);` combines the values with `f(old, new)`.
+///
+/// ```rust
+/// use velcro::hash_map;
+///
+/// let map = hash_map! { @keep_first; 'a': 0, ..('a'..='c'): 1 };
+/// assert_eq!(map[&'a'], 0);
+/// assert_eq!(map[&'c'], 1);
+///
+/// let totals = hash_map! {
+///     @merge(|old: u32, new| old + new);
+///     ..vec![("apples", 3), ("pears", 1)],
+///     "apples": 2,
+/// };
+/// assert_eq!(totals["apples"], 5);
+///```
+///
+/// The type of the closure's first argument often needs to be annotated, because it
+/// is called before the map's value type is known.
+///
+/// ```rust,should_panic
+/// use velcro::hash_map;
+///
+/// // panics: duplicate key in map literal: 'a'
+/// let map = hash_map! { @unique; ..['a', 'b']: 0, 'a': 1 };
+///```
+///
+/// A literal or path key that is written more than once is a compile error, unless
+/// `@keep_first;` or `@merge(f);` is given, since it is most likely a mistake.
+///
+/// ```compile_fail
+/// use velcro::hash_map;
+///
+/// let map = hash_map! { '.': 0, ',': 1, '.': 2 };
 ///```
-pub use velcro_macros::linked_list_from;
-
-/// An initializer for `HashMap`, allowing for entries to be specified individually
-/// or for the same value to be given to multiple keys using the `..` operator.
 ///
-/// # Usage
+/// # Hashers
+///
+/// The map uses `RandomState` unless a hasher is given with an `@hasher(state);`
+/// directive before the entries. Use `@hasher(Default::default());` to create the
+/// hasher type from a type annotation, such as an alias for a map with a faster
+/// hasher.
 ///
 /// ```rust
-/// # use std::collections::HashMap;
+/// use std::collections::hash_map::{DefaultHasher, RandomState};
+/// use std::collections::HashMap;
+/// use std::hash::BuildHasherDefault;
 /// use velcro::hash_map;
-/// let mut map1 = HashMap::new();
-/// map1.insert('a', 0);
-/// map1.insert('b', 1);
-/// map1.insert('c', 1);
-/// map1.insert('d', 1);
-/// map1.insert('e', 1);
-/// map1.insert('f', 2);
 ///
-/// let map2 = hash_map! {
+/// type MyHashMap<K, V> = HashMap<K, V, BuildHasherDefault<DefaultHasher>>;
+///
+/// let map: MyHashMap<_, _> = hash_map! {
+///     @hasher(Default::default());
 ///     'a': 0,
 ///     ..('b'..='e'): 1,
-///     'f': 2
 /// };
+/// assert_eq!(map[&'c'], 1);
 ///
-/// assert_eq!(map1, map2);
+/// let state = RandomState::new();
+/// let map = hash_map! { @hasher(state.clone()); "a": 0 };
+/// assert_eq!(map["a"], 0);
 ///```
 pub use velcro_macros::hash_map;
 
@@ -307,6 +1156,55 @@ pub use velcro_macros::hash_map;
 ///```
 pub use velcro_macros::hash_map_from;
 
+/// Works the same as `hash_map!`, except that the `?` operator may be used in keys,
+/// values and spreads, and the macro evaluates to a `Result<HashMap<K, V>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use std::num::ParseIntError;
+/// use velcro::{hash_map, try_hash_map};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let map: Result<HashMap<&str, u32>, ParseIntError> =
+///     try_hash_map! { "port": parse("8080")?, ..["a", "b"]: parse("0")? };
+/// assert_eq!(map, Ok(hash_map! { "port": 8080, "a": 0, "b": 0 }));
+///
+/// let map: Result<HashMap<&str, u32>, ParseIntError> = try_hash_map! { "port": parse("x")? };
+/// assert!(map.is_err());
+/// ```
+pub use velcro_macros::try_hash_map;
+
+/// Works the same as `hash_map_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<HashMap<K, V>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_hash_map!`](crate::try_hash_map).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use std::num::TryFromIntError;
+/// use velcro::{hash_map, hash_map_try_from};
+///
+/// let map: Result<HashMap<u16, String>, TryFromIntError> = hash_map_try_from! {
+///     1u64: "one",
+///     ..[2u32, 3]: "many",
+///     ..vec![(4u8, "four")],
+/// };
+/// assert_eq!(map.unwrap()[&4], "four");
+///
+/// let map: Result<HashMap<u16, String>, TryFromIntError> = hash_map_try_from! { 70000u64: "big" };
+/// assert!(map.is_err());
+/// ```
+pub use velcro_macros::hash_map_try_from;
+
 /// An initializer for `BTreeMap`, allowing for entries to be specified individually
 /// or for the same value to be given to multiple keys using the `..` operator.
 ///
@@ -332,6 +1230,68 @@ pub use velcro_macros::hash_map_from;
 ///
 /// assert_eq!(map1, map2);
 ///```
+///
+/// A spread entry without a value, `..other`, inserts all of the entries from
+/// another map, or from any iterator of `(key, value)` pairs.
+///
+/// ```rust
+/// use velcro::btree_map;
+///
+/// let defaults = btree_map! {
+///     "host": "localhost",
+///     "port": "80",
+/// };
+///
+/// let config = btree_map! {
+///     ..defaults,
+///     "port": "8080",
+/// };
+///
+/// assert_eq!(config.into_iter().collect::<Vec<_>>(), vec![("host", "localhost"), ("port", "8080")]);
+///```
+///
+/// # Duplicate keys
+///
+/// When a key is given more than once, the last value replaces the earlier ones.
+/// A directive before the entries changes this:
+///
+/// * `@keep_first;` keeps the first value for each key.
+/// * `@unique;` panics if a key is given more than once.
+/// * `@merge(f);` combines the values with `f(old, new)`.
+///
+/// ```rust
+/// use velcro::btree_map;
+///
+/// let map = btree_map! { @keep_first; 'a': 0, ..('a'..='c'): 1 };
+/// assert_eq!(map[&'a'], 0);
+/// assert_eq!(map[&'c'], 1);
+///
+/// let totals = btree_map! {
+///     @merge(|old: u32, new| old + new);
+///     ..vec![("apples", 3), ("pears", 1)],
+///     "apples": 2,
+/// };
+/// assert_eq!(totals["apples"], 5);
+///```
+///
+/// The type of the closure's first argument often needs to be annotated, because it
+/// is called before the map's value type is known.
+///
+/// ```rust,should_panic
+/// use velcro::btree_map;
+///
+/// // panics: duplicate key in map literal: 'a'
+/// let map = btree_map! { @unique; ..['a', 'b']: 0, 'a': 1 };
+///```
+///
+/// A literal or path key that is written more than once is a compile error, unless
+/// `@keep_first;` or `@merge(f);` is given, since it is most likely a mistake.
+///
+/// ```compile_fail
+/// use velcro::btree_map;
+///
+/// let map = btree_map! { '.': 0, ',': 1, '.': 2 };
+///```
 pub use velcro_macros::btree_map;
 
 /// An initializer for `BTreeMap` that works the same as `btree_map!` except that
@@ -374,6 +1334,396 @@ pub use velcro_macros::btree_map;
 ///```
 pub use velcro_macros::btree_map_from;
 
+/// Works the same as `btree_map!`, except that the `?` operator may be used in keys,
+/// values and spreads, and the macro evaluates to a `Result<BTreeMap<K, V>, E>`.
+/// See [`try_vec!`](crate::try_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use std::num::ParseIntError;
+/// use velcro::{btree_map, try_btree_map};
+///
+/// let parse = |s: &str| s.parse::<u32>();
+///
+/// let map: Result<BTreeMap<u32, &str>, ParseIntError> = try_btree_map! { parse("1")?: "a", 2: "b" };
+/// assert_eq!(map, Ok(btree_map! { 1: "a", 2: "b" }));
+///
+/// let map: Result<BTreeMap<u32, &str>, ParseIntError> = try_btree_map! { parse("x")?: "a" };
+/// assert!(map.is_err());
+/// ```
+pub use velcro_macros::try_btree_map;
+
+/// Works the same as `btree_map_from!`, except that values are converted into the
+/// collection's item type via an implementation of `TryFrom`, and the macro evaluates
+/// to a `Result<BTreeMap<K, V>, E>`. Conversion errors are converted into `E` with `From`. The
+/// `?` operator may be used in the same way as in [`try_btree_map!`](crate::try_btree_map).
+///
+/// The types of the collection and the error must be known at compile time, and
+/// usually this means an explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use std::num::TryFromIntError;
+/// use velcro::{btree_map, btree_map_try_from};
+///
+/// let map: Result<BTreeMap<u16, String>, TryFromIntError> = btree_map_try_from! {
+///     1u64: "one",
+///     ..[2u32, 3]: "many",
+/// };
+/// assert_eq!(map.unwrap()[&3], "many");
+/// ```
+pub use velcro_macros::btree_map_try_from;
+
+/// An initializer for a multimap, `HashMap<K, Vec<V>>`, where each entry appends its
+/// value to the list for its key, instead of replacing it.
+///
+/// All of the forms of entry in `hash_map!` are supported. In addition, an entry with a
+/// spread value, `key: ..values`, appends all of the values to the list for the key.
+/// Wrap a `RangeTo` value in parentheses to append it as one value: `key: (..5)`.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::hash_map_vec;
+///
+/// let extra_tags = vec!["new"];
+/// let tags = hash_map_vec! {
+///     "apple": "fruit",
+///     "apple": "red",
+///     ..["apple", "lime"]: "sour",
+///     "lime": ..extra_tags,
+///     ..vec![("fig", "fruit")],
+/// };
+///
+/// assert_eq!(tags["apple"], vec!["fruit", "red", "sour"]);
+/// assert_eq!(tags["lime"], vec!["sour", "new"]);
+/// assert_eq!(tags["fig"], vec!["fruit"]);
+/// ```
+///
+/// As in `hash_map!`, the map uses `RandomState` unless a hasher is given with an
+/// `@hasher(state);` directive, and `@hasher(Default::default());` creates the hasher
+/// type from a type annotation.
+///
+/// ```rust
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::collections::HashMap;
+/// use std::hash::BuildHasherDefault;
+/// use velcro::hash_map_vec;
+///
+/// type MyHashMap<K, V> = HashMap<K, V, BuildHasherDefault<DefaultHasher>>;
+///
+/// let tags: MyHashMap<_, Vec<_>> = hash_map_vec! {
+///     @hasher(Default::default());
+///     "apple": "fruit",
+///     "apple": "red",
+/// };
+/// assert_eq!(tags["apple"], vec!["fruit", "red"]);
+/// ```
+pub use velcro_macros::hash_map_vec;
+
+/// An initializer for a multimap, `BTreeMap<K, Vec<V>>`, where each entry appends its
+/// value to the list for its key, instead of replacing it. It works the same as
+/// [`hash_map_vec!`](crate::hash_map_vec).
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::btree_map_vec;
+///
+/// let routes = btree_map_vec! {
+///     "/": "index",
+///     "/users": ..["list_users", "create_user"],
+///     "/": "home",
+/// };
+///
+/// assert_eq!(routes["/"], vec!["index", "home"]);
+/// assert_eq!(routes["/users"], vec!["list_users", "create_user"]);
+/// ```
+pub use velcro_macros::btree_map_vec;
+
+/// Creates a `HashMap<T, usize>` of the number of times that each item occurs. Items
+/// can be given individually or "spread" using the `..` operator, and every item of a
+/// spread is counted.
+///
+/// An item followed by a count, `item: count` or `..items: count`, adds that count for
+/// the item instead of one.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::counter;
+///
+/// let words = vec!["a", "b", "a"];
+/// let counts = counter![..words, "c", "b": 3];
+///
+/// assert_eq!(counts["a"], 2);
+/// assert_eq!(counts["b"], 4);
+/// assert_eq!(counts["c"], 1);
+/// ```
+///
+/// As in `hash_map!`, the map uses `RandomState` unless a hasher is given with an
+/// `@hasher(state);` directive, and `@hasher(Default::default());` creates the hasher
+/// type from a type annotation.
+///
+/// ```rust
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::collections::HashMap;
+/// use std::hash::BuildHasherDefault;
+/// use velcro::counter;
+///
+/// type MyHashMap<K, V> = HashMap<K, V, BuildHasherDefault<DefaultHasher>>;
+///
+/// let counts: MyHashMap<_, _> = counter![@hasher(Default::default()); "a", "b", "a"];
+/// assert_eq!(counts["a"], 2);
+/// ```
+pub use velcro_macros::counter;
+
+/// Creates a `BTreeMap<T, usize>` of the number of times that each item occurs. It works
+/// the same as [`counter!`](crate::counter).
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::btree_counter;
+///
+/// let counts = btree_counter![..'a'..='c', ..['a', 'b']: 2];
+///
+/// assert_eq!(counts.into_iter().collect::<Vec<_>>(), vec![('a', 3), ('b', 3), ('c', 1)]);
+/// ```
+pub use velcro_macros::btree_counter;
+
+/// Creates a `String` by concatenating `char`s and strings, which can be given
+/// individually or "spread" using the `..` operator.
+///
+/// The items can be of any type that `String` implements `Extend` for, such as `char`,
+/// `&str` and `String`. The string is allocated with a capacity for the lengths of the
+/// items that are not spread, and of the separators between the items of spreads. The
+/// lengths of the items of a spread are not known until they are added, so one byte is
+/// allowed for each of them, according to the lower bound of its `size_hint`, and the
+/// string may grow as they are added.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::string;
+///
+/// let name = "velcro";
+/// let parts = vec!["src", "lib.rs"];
+///
+/// assert_eq!(string!["crates/", name, '/', ..parts], "crates/velcro/srclib.rs");
+/// ```
+///
+/// The items of each spread can be joined with a separator, given by an
+/// `@sep(separator);` directive. The separator is only added between the items of a
+/// spread, not between other items.
+///
+/// ```rust
+/// use velcro::string;
+///
+/// let names = vec!["a", "b", "c"];
+/// assert_eq!(string![@sep(", "); "[", ..names, "]"], "[a, b, c]");
+/// ```
+pub use velcro_macros::string;
+
+/// Creates a `Vec<u8>` from bytes, byte strings, encoded integers and spreads of bytes.
+///
+/// Each item may be:
+///
+/// * A byte string literal, such as `b"HDR"`, which is added in full.
+/// * A hex string, such as `hex("0a ff")`, which is checked and decoded at compile time.
+///   Whitespace between pairs of digits is ignored.
+/// * An integer or float encoded with a byte order, using one of `u16_le`, `u16_be`,
+///   `u32_le`, `u32_be` and so on, for each of `u16`, `u32`, `u64`, `u128`, `i16`,
+///   `i32`, `i64`, `i128`, `f32` and `f64`.
+/// * Any other expression, which must be a `u8`.
+/// * A spread of bytes, with the `..` operator, which can be any iterable of `u8` or
+///   `&u8`, including arrays and slices.
+///
+/// The vector is allocated with a capacity for all of the items that are not spread,
+/// plus the lower bound of the `size_hint` of each spread.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::bytes;
+///
+/// let payload = [0xaa, 0xbb];
+/// let len = payload.len() as u16;
+/// let crc: u32 = 0x01020304;
+///
+/// let buffer = bytes![b"HDR", 0x01, ..&payload, u16_le(len), ..crc.to_be_bytes()];
+///
+/// assert_eq!(
+///     buffer,
+///     vec![b'H', b'D', b'R', 0x01, 0xaa, 0xbb, 0x02, 0x00, 0x01, 0x02, 0x03, 0x04]
+/// );
+/// assert_eq!(bytes![hex("cafe f00d")], vec![0xca, 0xfe, 0xf0, 0x0d]);
+/// ```
+///
+/// Hex strings that are not valid are a compile error:
+///
+/// ```compile_fail
+/// use velcro::bytes;
+///
+/// let buffer = bytes![hex("abc")];
+/// ```
+pub use velcro_macros::bytes;
+
+/// Creates a fixed-size array, `[T; N]`, from values and spreads of arrays, where the
+/// length `N` is computed at compile time.
+///
+/// Each spread must have a length that is known at compile time, so it must be an
+/// array literal, `..[a, b]`, a repeated array, `..[value; len]`, or an array
+/// constant, `..TABLE`. Other spreads, and conditional elements, are a compile error.
+/// A constant is recognised by its upper case name, so a local array variable can't be
+/// spread, even though its length is known. The macro can be used to initialize `const`
+/// and `static` items.
+///
+/// If the input contains only values and spreads of array literals, the result is
+/// just an array literal. Otherwise, the array is filled by copying, so the values must
+/// be `Copy`.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::array;
+///
+/// const HEADER: [u8; 2] = [0xca, 0xfe];
+/// static TABLE: [u8; 6] = array![..HEADER, 1, ..[2, 3], ..[0; 1]];
+///
+/// assert_eq!(TABLE, [0xca, 0xfe, 1, 2, 3, 0]);
+/// assert_eq!(array![1, 2, ..[3, 4, 5]], [1, 2, 3, 4, 5]);
+/// ```
+///
+/// A spread whose length is only known at runtime is a compile error:
+///
+/// ```compile_fail
+/// use velcro::array;
+///
+/// let values = vec![1, 2, 3];
+/// let array = array![0, ..values];
+/// ```
+///
+/// ```compile_fail
+/// use velcro::array;
+///
+/// let values = [1, 2, 3];
+/// let array = array![0, ..values];
+/// ```
+pub use velcro_macros::array;
+
+/// Creates a `Box<[T]>` from the given values. Other collections and iterators may
+/// also be interspersed, or "spread", using the `..` operator.
+///
+/// The slice is allocated once, at its final size, as long as the length of each
+/// spread is known up front, as it is for arrays, ranges and the iterators of most
+/// collections. Spreads whose length isn't known, such as filtered iterators and
+/// comprehensions, may cause it to be reallocated.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::boxed_slice;
+///
+/// let values = vec![4, 5];
+/// let slice: Box<[i32]> = boxed_slice![1, ..[2, 3], ..values];
+///
+/// assert_eq!(*slice, [1, 2, 3, 4, 5]);
+/// ```
+pub use velcro_macros::boxed_slice;
+
+/// Creates an `Rc<[T]>` from the given values. It works the same as
+/// [`boxed_slice!`](crate::boxed_slice).
+///
+/// The slice is allocated once, at its final size, if each spread is an array, a `Vec`,
+/// a range or a slice iterator and no element is conditional. Otherwise, the values are
+/// collected into a `Vec` first.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use velcro::rc_slice;
+///
+/// let slice: Rc<[char]> = rc_slice!['a', ..'b'..='d'];
+///
+/// assert_eq!(*slice, ['a', 'b', 'c', 'd']);
+/// ```
+pub use velcro_macros::rc_slice;
+
+/// Creates an `Arc<[T]>` from the given values. It works the same as
+/// [`boxed_slice!`](crate::boxed_slice).
+///
+/// The slice is allocated once, at its final size, if each spread is an array, a `Vec`,
+/// a range or a slice iterator and no element is conditional. Otherwise, the values are
+/// collected into a `Vec` first.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use velcro::arc_slice;
+///
+/// let slice: Arc<[&str]> = arc_slice!["a", ..vec!["b", "c"]];
+///
+/// assert_eq!(*slice, ["a", "b", "c"]);
+/// ```
+pub use velcro_macros::arc_slice;
+
+/// An initializer for `Box<[T]>` that works the same as `boxed_slice!` except that
+/// values can be of any type that can be converted into the slice's item type via an
+/// `Into` implementation.
+///
+/// The type of the item must be known at compile time, and usually this means an
+/// explicit type annotation is required.
+///
+/// # Usage
+///
+/// ```rust
+/// use velcro::boxed_slice_from;
+///
+/// let slice: Box<[i64]> = boxed_slice_from![1i32, 2u8, ..vec![3u16, 4]];
+///
+/// assert_eq!(*slice, [1, 2, 3, 4]);
+/// ```
+pub use velcro_macros::boxed_slice_from;
+
+/// An initializer for `Rc<[T]>` that works the same as `rc_slice!` except that values
+/// can be of any type that can be converted into the slice's item type via an `Into`
+/// implementation.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use velcro::rc_slice_from;
+///
+/// let slice: Rc<[String]> = rc_slice_from!["a", ..["b", "c"]];
+///
+/// assert_eq!(*slice, ["a", "b", "c"]);
+/// ```
+pub use velcro_macros::rc_slice_from;
+
+/// An initializer for `Arc<[T]>` that works the same as `arc_slice!` except that
+/// values can be of any type that can be converted into the slice's item type via an
+/// `Into` implementation.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use velcro::arc_slice_from;
+///
+/// let slice: Arc<[f64]> = arc_slice_from![1.5f32, ..[2u8, 3]];
+///
+/// assert_eq!(*slice, [1.5, 2.0, 3.0]);
+/// ```
+pub use velcro_macros::arc_slice_from;
+
 /// Creates an iterator, over the given values. Other collections and iterators
 /// may also be interspersed, or "spread", using the `..` operator.
 ///
@@ -391,6 +1741,27 @@ pub use velcro_macros::btree_map_from;
 ///
 /// assert_eq!(iter![0, 1, ..(2..=5), 6].collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 6]);
 ///```
+///
+/// Comprehensions are lazy: their elements are only evaluated as the iterator is
+/// consumed. Each `for` clause becomes a closure. The first clause borrows the
+/// variables that it uses, like any other closure, but the clauses nested inside it are
+/// `move` closures, because they must own the items bound by the outer patterns. So a
+/// variable that is used by a nested clause is moved into the iterator, unless it is
+/// bound to a reference first.
+///
+/// ```rust
+/// use velcro::iter;
+///
+/// let xs = vec![1, 2];
+/// let ys = vec![10, 20];
+/// let ys_ref = &ys;
+/// let mut it = iter![for x in &xs for y in ys_ref => x + y, for z in 0.. => z];
+///
+/// assert_eq!(it.by_ref().take(4).collect::<Vec<_>>(), vec![11, 21, 12, 22]);
+/// assert_eq!(it.next(), Some(0));
+/// // `xs` and `ys` were only borrowed
+/// assert_eq!(xs.len() + ys.len(), 4);
+///```
 pub use velcro_macros::iter;
 
 /// Creates an iterator, over the given values. Works the same as `iter` except that values
@@ -440,6 +1811,20 @@ pub use velcro_macros::iter_from;
 /// );
 ///```
 ///
+/// A spread entry without a value, `..pairs`, chains an iterator of `(key, value)`
+/// pairs.
+///
+/// ```rust
+/// use velcro::map_iter;
+///
+/// let pairs = vec![(1, "b"), (2, "c")];
+///
+/// assert_eq!(
+///     map_iter![0: "a", ..pairs, 3: "d"].collect::<Vec<_>>(),
+///     vec![(0, "a"), (1, "b"), (2, "c"), (3, "d")]
+/// );
+///```
+///
 /// A typical use-case for `map_iter` is to collect into a third party map
 /// implementation, not supported by velcro, while still being able to use the
 /// velcro spread operator.
@@ -486,3 +1871,7 @@ pub use velcro_macros::map_iter;
 ///     ]);
 ///```
 pub use velcro_macros::map_iter_from;
+
+#[doc(hidden)]
+#[path = "private.rs"]
+pub mod __private;
@@ -0,0 +1,286 @@
+//! Items used by the code that the macros generate. They are not part of the public API
+//! and may change in any release.
+
+use core::convert::Infallible;
+
+pub extern crate alloc;
+
+#[cfg(feature = "std")]
+pub use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "hashbrown")]
+pub extern crate hashbrown;
+
+pub use crate::__velcro_require_hashbrown as require_hashbrown;
+pub use crate::__velcro_require_std as require_std;
+
+/// Expands to the output of a macro that creates a `HashMap` or `HashSet` of `std`, or
+/// to a compile error if they are not available. The features are checked here, rather
+/// than in the proc macros, because they must be those of this crate.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __velcro_require_std {
+    ($collection:literal, $output:expr) => {
+        $output
+    };
+}
+
+// Without the feature, the output is replaced by the error
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __velcro_require_std {
+    ($collection:literal, $output:expr) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            $collection,
+            "` is only available with the `std` feature of velcro, or with the \
+             `hashbrown` feature and an `@hashbrown;` directive"
+        ))
+    };
+}
+
+/// Expands to the output of a macro with the `@hashbrown;` directive, or to a compile
+/// error if the `hashbrown` feature is not enabled.
+#[cfg(feature = "hashbrown")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __velcro_require_hashbrown {
+    ($collection:literal, $output:expr) => {
+        $output
+    };
+}
+
+#[cfg(not(feature = "hashbrown"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __velcro_require_hashbrown {
+    ($collection:literal, $output:expr) => {
+        ::core::compile_error!(
+            "`@hashbrown` is only available with the `hashbrown` feature of velcro"
+        )
+    };
+}
+
+/// The types that the `?` operator can be applied to in a `try_` macro. This mirrors the
+/// unstable `std::ops::Try` trait, for `Result` and `Option`.
+pub trait Try {
+    type Output;
+    type Residual;
+
+    fn branch(self) -> Result<Self::Output, Self::Residual>;
+
+    fn from_output(output: Self::Output) -> Self;
+}
+
+/// Converts what is left over when `?` does not produce a value into the output of a
+/// `try_` macro. Errors are converted with `From`, in the same way as `?`.
+pub trait FromResidual<R> {
+    fn from_residual(residual: R) -> Self;
+}
+
+impl<T, E> Try for Result<T, E> {
+    type Output = T;
+    type Residual = Result<Infallible, E>;
+
+    fn branch(self) -> Result<T, Result<Infallible, E>> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(Err(error)),
+        }
+    }
+
+    fn from_output(output: T) -> Self {
+        Ok(output)
+    }
+}
+
+impl<T, E, F: From<E>> FromResidual<Result<Infallible, E>> for Result<T, F> {
+    fn from_residual(residual: Result<Infallible, E>) -> Self {
+        match residual {
+            Ok(never) => match never {},
+            Err(error) => Err(From::from(error)),
+        }
+    }
+}
+
+impl<T> Try for Option<T> {
+    type Output = T;
+    type Residual = Option<Infallible>;
+
+    fn branch(self) -> Result<T, Option<Infallible>> {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(None),
+        }
+    }
+
+    fn from_output(output: T) -> Self {
+        Some(output)
+    }
+}
+
+impl<T> FromResidual<Option<Infallible>> for Option<T> {
+    fn from_residual(_: Option<Infallible>) -> Self {
+        None
+    }
+}
+
+/// Unwraps the value of `?`, or returns the output of the `try_` macro that `?` stops it
+/// with.
+pub fn branch<T, R>(value: T) -> Result<T::Output, R>
+where
+    T: Try,
+    R: FromResidual<T::Residual>,
+{
+    match value.branch() {
+        Ok(value) => Ok(value),
+        Err(residual) => Err(R::from_residual(residual)),
+    }
+}
+
+/// Creates an iterator over the items of a conditional element, which are only there if
+/// the condition held. Unlike `Iterator::flatten`, it knows the length of the items up
+/// front, so that collecting a slice does not have to guess its size.
+pub fn optional<I: IntoIterator>(items: Option<I>) -> Optional<I::IntoIter> {
+    Optional(items.map(IntoIterator::into_iter))
+}
+
+/// The iterator returned by [`optional`].
+pub struct Optional<I>(Option<I>);
+
+impl<I: Iterator> Iterator for Optional<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Some(iter) => iter.size_hint(),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+/// Relates the type of an item removed from a set to the type of the set's items, which
+/// can't be inferred from `remove` if the item is converted.
+pub fn same_item_type<'a, C, T: 'a>(_: &'a C, _: &T)
+where
+    &'a C: IntoIterator<Item = &'a T>,
+{
+}
+
+/// Relates the type of a key removed from a map to the type of the map's keys, as
+/// [`same_item_type`] does for sets.
+pub fn same_key_type<'a, C, K: 'a, V: 'a>(_: &'a C, _: &K)
+where
+    &'a C: IntoIterator<Item = (&'a K, &'a V)>,
+{
+}
+
+/// Pairs each key with the value that `f` returns for it. The closure is passed
+/// directly to a function that relates its argument to the key type, because the type
+/// of a closure's arguments is not inferred from later calls, so methods could not be
+/// called on the key.
+pub fn per_key<I, F, T>(keys: I, mut f: F) -> impl Iterator<Item = (I::Item, T)>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> T,
+{
+    keys.into_iter().map(move |key| {
+        let value = f(&key);
+        (key, value)
+    })
+}
+
+/// Pairs each value with the key that `f` returns for it. As in [`per_key`], the type of
+/// the closure's argument is inferred from the values.
+pub fn keyed<I, F, K>(values: I, mut f: F) -> impl Iterator<Item = (K, I::Item)>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> K,
+{
+    values.into_iter().map(move |value| (f(&value), value))
+}
+
+/// Returns the length of an array in a constant expression, for `array!`.
+pub const fn array_len<T, const N: usize>(_: &[T; N]) -> usize {
+    N
+}
+
+/// Returns the first value of an array in a constant expression, for `array!`.
+pub const fn array_first<T: Copy, const N: usize>(array: &[T; N]) -> Option<T> {
+    if N > 0 {
+        Some(array[0])
+    } else {
+        None
+    }
+}
+
+/// Creates the array that `array!` copies the values of constants into, filled with the
+/// first of their values. There is no first value only if all of the constants are
+/// empty, in which case the array is empty too.
+pub const fn array_filled<T: Copy, const N: usize>(first: Option<T>) -> [T; N] {
+    match first {
+        Some(value) => [value; N],
+        None => {
+            assert!(N == 0, "`array!` can't fill an array without a value");
+            // SAFETY: an empty array has no values to initialize
+            #[allow(clippy::uninit_assumed_init)]
+            unsafe {
+                core::mem::MaybeUninit::<[T; N]>::uninit().assume_init()
+            }
+        }
+    }
+}
+
+/// The types of the items that `string!` can add to a `String`, which are those that
+/// `String` implements `Extend` for.
+pub trait StrLen {
+    /// Returns the length of the item in bytes.
+    fn str_len(&self) -> usize;
+}
+
+impl StrLen for char {
+    fn str_len(&self) -> usize {
+        self.len_utf8()
+    }
+}
+
+impl StrLen for str {
+    fn str_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl StrLen for alloc::string::String {
+    fn str_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl StrLen for alloc::boxed::Box<str> {
+    fn str_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl StrLen for alloc::borrow::Cow<'_, str> {
+    fn str_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: StrLen + ?Sized> StrLen for &T {
+    fn str_len(&self) -> usize {
+        (**self).str_len()
+    }
+}
+
+/// Returns the length in bytes of an item of `string!`.
+pub fn str_len<T: StrLen + ?Sized>(item: &T) -> usize {
+    item.str_len()
+}
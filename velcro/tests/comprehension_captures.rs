@@ -0,0 +1,21 @@
+//! Tests for the variables captured by the closures of lazy comprehensions.
+use velcro::{iter, map_iter};
+
+#[test]
+fn lazy_comprehension_borrows_outer_variables() {
+    let xs = vec![1, 2];
+    let scale = String::from("x");
+    let doubled: Vec<_> = iter![for x in &xs => (x * 2, scale.len())].collect();
+    assert_eq!(doubled, vec![(2, 1), (4, 1)]);
+    let map: Vec<_> = map_iter! { for x in &xs => *x: scale.len() }.collect();
+    assert_eq!(map, vec![(1, 1), (2, 1)]);
+    // `scale` was borrowed, not moved
+    assert_eq!(scale, "x");
+}
+
+#[test]
+fn nested_lazy_comprehension_uses_outer_pattern() {
+    let xs = vec![1, 2];
+    let pairs: Vec<_> = iter![for x in &xs for y in 0..*x => (*x, y)].collect();
+    assert_eq!(pairs, vec![(1, 0), (2, 0), (2, 1)]);
+}
@@ -0,0 +1,54 @@
+//! Tests for `array!` in `const` and `static` items, and for how often the values of
+//! arrays that are built by copying are evaluated.
+use std::cell::Cell;
+use velcro::array;
+
+const HEADER: [u8; 3] = [0xca, 0xfe, 0x01];
+const EMPTY: [u8; 0] = [];
+const WIDE: [i32; 2] = [10, 20];
+const TABLE: [u8; 7] = array![..HEADER, 0, ..[1, 2], ..EMPTY, ..[9; 1]];
+static LOOKUP: [u8; 5] = array![..[0; 2], ..HEADER];
+const AFTER_EMPTY: [u8; 5] = array![..EMPTY, ..HEADER, ..EMPTY, ..[1; 2]];
+const ONLY_EMPTY: [u8; 0] = array![..EMPTY, ..EMPTY];
+
+#[test]
+fn array_in_const_and_static_items() {
+    assert_eq!(TABLE, [0xca, 0xfe, 0x01, 0, 1, 2, 9]);
+    assert_eq!(LOOKUP, [0, 0, 0xca, 0xfe, 0x01]);
+    assert_eq!(AFTER_EMPTY, [0xca, 0xfe, 0x01, 1, 1]);
+    assert_eq!(ONLY_EMPTY, []);
+}
+
+#[test]
+fn array_starting_with_empty_constant() {
+    assert_eq!(array![..EMPTY, ..[1u8; 2]], [1, 1]);
+    assert_eq!(array![..EMPTY, 7, ..HEADER], [7, 0xca, 0xfe, 0x01]);
+    assert_eq!(array![..EMPTY, ..EMPTY, ..HEADER], HEADER);
+    assert_eq!(array![..EMPTY], [0u8; 0]);
+}
+
+#[test]
+fn array_with_only_literals_should_not_need_copy() {
+    let strings = array![String::from("a"), ..[String::from("b"), String::from("c")]];
+    assert_eq!(strings, ["a", "b", "c"]);
+}
+
+#[test]
+fn copied_array_values_should_be_evaluated_once() {
+    let count = Cell::new(0);
+    let next = || {
+        count.set(count.get() + 1);
+        count.get()
+    };
+    let values = array![next(), ..[next(), next()], ..[next(); 2], ..WIDE];
+    assert_eq!(count.get(), 4);
+    assert_eq!(values, [1, 2, 3, 4, 4, 10, 20]);
+}
+
+#[test]
+fn array_values_can_call_functions_named_like_helpers() {
+    const fn len(value: u8) -> u8 {
+        value
+    }
+    assert_eq!(array![len(1), ..HEADER], [1, 0xca, 0xfe, 0x01]);
+}
@@ -0,0 +1,40 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::num::TryFromIntError;
+use velcro::{btree_map_from, hash_map_try_from, vec_deque_try_from, vec_from, vec_try_from};
+
+// The item types of converted spreads can only be inferred from the collection if
+// there is no other `Extend` implementation that could apply, such as for `&T`.
+
+#[test]
+fn vec_from_spread_of_integers() {
+    let v: Vec<u64> = vec_from![1u8, ..[2u16, 3]];
+    assert_eq!(v, vec![1, 2, 3]);
+}
+
+#[test]
+fn btree_map_from_spread_of_integer_pairs() {
+    let m: BTreeMap<u64, String> = btree_map_from! { ..vec![(1u8, "a")], 2u32: "b" };
+    assert_eq!(m[&1], "a");
+}
+
+#[test]
+fn vec_try_from_spread_of_integers() {
+    let v: Result<Vec<u8>, TryFromIntError> = vec_try_from![1u64, ..[2u16, 3], ..vec![4i32]];
+    assert_eq!(v, Ok(vec![1, 2, 3, 4]));
+    let v: Result<Vec<u8>, TryFromIntError> = vec_try_from![1u64, ..[2u16, 300]];
+    assert!(v.is_err());
+}
+
+#[test]
+fn vec_deque_try_from_spread_to_front() {
+    let d: Result<VecDeque<u8>, TryFromIntError> = vec_deque_try_from![1u64, <- ..[2u16, 3]];
+    assert_eq!(d, Ok(VecDeque::from(vec![2, 3, 1])));
+}
+
+#[test]
+fn hash_map_try_from_spread_of_integer_pairs() {
+    let m: Result<HashMap<u8, u8>, TryFromIntError> = hash_map_try_from! { ..vec![(1u64, 2u32)] };
+    assert_eq!(m.unwrap()[&1], 2);
+    let m: Result<HashMap<u8, u8>, TryFromIntError> = hash_map_try_from! { ..vec![(1u64, 256u32)] };
+    assert!(m.is_err());
+}
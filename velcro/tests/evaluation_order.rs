@@ -0,0 +1,57 @@
+//! Tests that the elements of a literal are evaluated in source order, even though
+//! spreads are evaluated before the collection is allocated.
+use std::cell::RefCell;
+use velcro::{bytes, hash_map, hash_set, string, vec, vec_deque};
+
+#[test]
+fn single_value_before_spread_of_same_vec() {
+    let mut a = vec![1, 2, 3];
+    let v = vec![a.pop().unwrap(), ..a];
+    assert_eq!(v, vec![3, 1, 2]);
+}
+
+#[test]
+fn elements_are_evaluated_in_source_order() {
+    let log = RefCell::new(Vec::new());
+    let one = |name: &'static str| {
+        log.borrow_mut().push(name);
+        1u8
+    };
+    let many = |name: &'static str| {
+        log.borrow_mut().push(name);
+        vec![2u8]
+    };
+
+    let _ = vec![one("a"), ..many("b"), one("c"), ..many("d")];
+    assert_eq!(log.replace(Vec::new()), ["a", "b", "c", "d"]);
+
+    let _ = vec![one("a"), if true => one("b"), ..many("c")];
+    assert_eq!(log.replace(Vec::new()), ["a", "b", "c"]);
+
+    let _ = vec_deque![one("a"), <- one("b"), ..many("c")];
+    assert_eq!(log.replace(Vec::new()), ["a", "b", "c"]);
+
+    let _ = hash_set![one("a"), ..many("b"), one("c")];
+    assert_eq!(log.replace(Vec::new()), ["a", "b", "c"]);
+
+    let _ = hash_map! { one("a"): one("b"), ..many("c"): one("d"), ..[(3, 4)] };
+    assert_eq!(log.replace(Vec::new()), ["a", "b", "c", "d"]);
+
+    let _ = bytes![one("a"), ..many("b")];
+    assert_eq!(log.replace(Vec::new()), ["a", "b"]);
+
+    let _ = string![
+        char::from(one("a")),
+        ..many("b").into_iter().map(char::from)
+    ];
+    assert_eq!(log.replace(Vec::new()), ["a", "b"]);
+}
+
+#[test]
+fn front_spread_evaluated_in_place_sees_user_variables() {
+    let len = 3;
+    let c = true;
+    let xs = [7, 8, 9];
+    let deque = vec_deque![if c => 1, <- ..xs.iter().copied().take(len)];
+    assert_eq!(deque, [7, 8, 9, 1]);
+}
@@ -0,0 +1,87 @@
+//! Tests for keys and values containing `:` and `,` tokens, which must not be mistaken
+//! for the delimiters between entries.
+use velcro::{btree_map, hash_map, map_iter};
+
+#[derive(Debug, Default, PartialEq)]
+struct Pair<A, B>(A, B);
+
+impl<A, B> Pair<A, B> {
+    fn new(a: A, b: B) -> Self {
+        Pair(a, b)
+    }
+}
+
+fn apply(f: impl Fn(u8, u8) -> u8, a: u8, b: u8) -> u8 {
+    f(a, b)
+}
+
+#[test]
+fn values_with_generic_arguments_should_be_permitted() {
+    let map = hash_map! {
+        "a": Pair::<u8, char>::new(1, 'x'),
+        "b": Pair::<u8, char>::default(),
+    };
+
+    assert_eq!(map["a"], Pair(1, 'x'));
+    assert_eq!(map["b"], Pair(0, '\0'));
+}
+
+#[test]
+fn keys_with_generic_arguments_should_be_permitted() {
+    let map = btree_map! {
+        <Vec<(u8, u8)>>::new(): 1,
+        ..[<Vec<(u8, u8)>>::from([(1, 2)])]: 2,
+    };
+
+    assert_eq!(map[&vec![]], 1);
+    assert_eq!(map[&vec![(1, 2)]], 2);
+}
+
+#[test]
+fn closure_values_should_be_permitted() {
+    let map = hash_map! {
+        "add": apply(|a, b| a + b, 3, 2),
+        "sub": apply(|a: u8, b: u8| a - b, 3, 2),
+    };
+
+    assert_eq!(map["add"], 5);
+    assert_eq!(map["sub"], 1);
+}
+
+#[test]
+fn closure_keys_should_be_permitted() {
+    let items: Vec<_> = map_iter! {
+        |x: u8, y: u8| x + y: "add",
+    }
+    .map(|(f, name)| (f(3, 2), name))
+    .collect();
+
+    assert_eq!(items, vec![(5, "add")]);
+
+    let map = hash_map! {
+        apply(|x: u8, y: u8| x + y, 1, 2): "three",
+        ..[2u8].iter().flat_map(|x: &u8| x * 2..x * 3): "range",
+    };
+
+    assert_eq!(map[&3], "three");
+    assert_eq!(map[&4], "range");
+    assert_eq!(map[&5], "range");
+}
+
+#[test]
+fn nested_macros_should_be_permitted() {
+    let map = hash_map! {
+        "a": hash_map! { 1: vec![1, 2], 2: vec![3; 2] },
+        "b": hash_map! { ..vec![(1, vec![4, 5])] },
+    };
+
+    assert_eq!(map["a"][&1], vec![1, 2]);
+    assert_eq!(map["a"][&2], vec![3, 3]);
+    assert_eq!(map["b"][&1], vec![4, 5]);
+
+    let map = btree_map! {
+        format!("{}:{}", 1, 2): format!("{}, {}", 3, 4),
+    };
+
+    assert_eq!(map["1:2"], "3, 4");
+}
@@ -0,0 +1,24 @@
+//! Tests for the `@hashbrown;` directive, which needs the `hashbrown` feature.
+#![cfg(feature = "hashbrown")]
+use velcro::{counter, hash_map, hash_map_vec, hash_set};
+
+#[test]
+fn hashbrown_directive_creates_hashbrown_collections() {
+    let map: hashbrown::HashMap<_, _> = hash_map! { @hashbrown; 'a': 1, ..[('b', 2)] };
+    assert_eq!(map[&'b'], 2);
+
+    let set: hashbrown::HashSet<_> = hash_set![@hashbrown; 1, ..2..4];
+    assert_eq!(set.len(), 3);
+
+    let counts: hashbrown::HashMap<_, usize> = counter![@hashbrown; 'a', 'a', 'b'];
+    assert_eq!(counts[&'a'], 2);
+
+    let lists: hashbrown::HashMap<_, Vec<_>> = hash_map_vec! { @hashbrown; 'a': 1, 'a': 2 };
+    assert_eq!(lists[&'a'], [1, 2]);
+}
+
+#[test]
+fn collections_of_std_are_still_created_without_the_directive() {
+    let map: std::collections::HashMap<_, _> = hash_map! { 'a': 1 };
+    assert_eq!(map[&'a'], 1);
+}
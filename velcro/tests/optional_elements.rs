@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+use std::num::ParseIntError;
+use velcro::{btree_map, btree_set, map_iter, try_btree_map, try_vec, vec};
+
+#[test]
+fn optional_values_are_added_only_if_some() {
+    let (maybe, none): (Option<u8>, Option<u8>) = (Some(2), None);
+    assert_eq!(vec![1, ?maybe, ?none, 3], vec![1, 2, 3]);
+    assert_eq!(btree_set![?none, ?maybe], btree_set![2]);
+}
+
+#[test]
+fn optional_entries_are_added_only_if_some() {
+    let (opt, none): (Option<&str>, Option<&str>) = (Some("v"), None);
+    let map = btree_map! { "a": "x", "k": ?opt, "n": ?none };
+    assert_eq!(map, btree_map! { "a": "x", "k": "v" });
+
+    let pairs: Vec<_> = map_iter! { "k": ?opt, "n": ?none }.collect();
+    assert_eq!(pairs, vec![("k", "v")]);
+}
+
+#[test]
+fn optional_entry_key_is_evaluated_even_if_none() {
+    let mut keys = 0;
+    let mut key = || {
+        keys += 1;
+        keys
+    };
+    let none: Option<&str> = None;
+    let map = btree_map! { key(): ?none, key(): "b" };
+    assert_eq!(map, btree_map! { 2: "b" });
+}
+
+#[test]
+fn try_operator_on_key_is_not_an_optional_entry() {
+    let parse = |s: &str| s.parse::<u32>();
+    let opt = Some("v");
+    let map: Result<BTreeMap<u32, &str>, ParseIntError> =
+        try_btree_map! { parse("1")?: "a", parse("2")?: ?opt, 3: ?opt.filter(|_| false) };
+    assert_eq!(map, Ok(btree_map! { 1: "a", 2: "v" }));
+
+    let values: Result<Vec<u32>, ParseIntError> = try_vec![parse("1")?, ?Some(parse("2")?)];
+    assert_eq!(values, Ok(vec![1, 2]));
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use velcro::{btree_map, btree_set, hash_map};
+
+#[test]
+fn tilde_removes_items_added_earlier() {
+    let set = btree_set![..(0..10), ~3, ~..(5..8), 6];
+    assert_eq!(set, btree_set![0, 1, 2, 4, 6, 8, 9]);
+
+    let map: HashMap<_, _> = hash_map! { ..('a'..='d'): 0, ~'b', ~..['c'] };
+    assert_eq!(map.len(), 2);
+    assert!(map.contains_key(&'a'));
+}
+
+#[test]
+fn exclamation_mark_negates_items() {
+    let flag = true;
+    let set = btree_set![!flag, flag];
+    assert_eq!(set, btree_set![false, true]);
+
+    let map = btree_map! { !flag: 0, flag: !0u8 };
+    assert_eq!(map[&false], 0);
+    assert_eq!(map[&true], 255);
+}
+
+#[test]
+fn removed_items_can_call_functions_named_like_helpers() {
+    fn same_type(value: u8) -> u8 {
+        value
+    }
+    let set = btree_set![1u8, 2, ~same_type(1)];
+    assert_eq!(set, btree_set![2]);
+
+    let map = btree_map! { 1u8: 'a', 2: 'b', ~..[same_type(2)] };
+    assert_eq!(map, btree_map! { 1: 'a' });
+}
@@ -0,0 +1,93 @@
+//! Tests how many times boxed, `Rc` and `Arc` slices are allocated, depending on what
+//! is known about the lengths of their spreads.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Arc;
+use velcro::{arc_slice, boxed_slice, boxed_slice_from, rc_slice};
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    (value, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn slices_should_be_allocated_once() {
+    let values = vec![4, 5];
+    let (slice, count) = allocations(|| boxed_slice![1, ..[2, 3], ..values.iter().copied()]);
+    assert_eq!(*slice, [1, 2, 3, 4, 5]);
+    assert_eq!(count, 1);
+
+    let (slice, count): (Rc<[i32]>, _) = allocations(|| rc_slice![1, 2, ..3..6]);
+    assert_eq!(*slice, [1, 2, 3, 4, 5]);
+    assert_eq!(count, 1);
+
+    let (slice, count): (Arc<[i32]>, _) = allocations(|| arc_slice![1, 2, 3]);
+    assert_eq!(*slice, [1, 2, 3]);
+    assert_eq!(count, 1);
+
+    let (slice, count): (Box<[i64]>, _) = allocations(|| boxed_slice_from![1i32, ..values.clone()]);
+    assert_eq!(*slice, [1, 4, 5]);
+    // one for the clone of `values`
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn boxed_slices_with_conditions_should_be_allocated_once() {
+    let values = [3, 4, 5];
+    let (slice, count) = allocations(|| boxed_slice![1, if true => 2, ..values.iter().copied()]);
+    assert_eq!(*slice, [1, 2, 3, 4, 5]);
+    assert_eq!(count, 1);
+
+    let (slice, count) = allocations(|| boxed_slice![if false => 1 else ..[2, 3], 4]);
+    assert_eq!(*slice, [2, 3, 4]);
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn boxed_slices_with_exact_spreads_should_be_allocated_once() {
+    let set: HashSet<i32> = [2].iter().copied().collect();
+    let (slice, count) = allocations(|| boxed_slice![1, ..set.iter().copied()]);
+    assert_eq!(*slice, [1, 2]);
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn shared_slices_with_inexact_spreads_should_be_collected_first() {
+    let set: HashSet<i32> = [2].iter().copied().collect();
+    // one for the `Vec` that the values are collected into, and one for the slice
+    let (slice, count): (Rc<[i32]>, _) = allocations(|| rc_slice![1, ..set.iter().copied()]);
+    assert_eq!(*slice, [1, 2]);
+    assert_eq!(count, 2);
+
+    let (slice, count): (Arc<[i32]>, _) = allocations(|| arc_slice![1, if true => 2]);
+    assert_eq!(*slice, [1, 2]);
+    assert_eq!(count, 2);
+}
@@ -0,0 +1,57 @@
+//! Tests for the values of spread map entries, `..keys: value`, which are evaluated
+//! once, or computed for each key when the value is a closure.
+use std::cell::Cell;
+use velcro::{btree_map, hash_map, map_iter};
+
+#[test]
+fn spread_entry_value_should_be_evaluated_once() {
+    let count = Cell::new(0);
+    let next = || {
+        count.set(count.get() + 1);
+        vec![count.get()]
+    };
+    let map = hash_map! { ..['a', 'b', 'c']: next() };
+    assert_eq!(count.get(), 1);
+    assert_eq!(map[&'c'], vec![1]);
+
+    let map = btree_map! { ..['a', 'b', 'c']: next() };
+    assert_eq!(count.get(), 2);
+    assert_eq!(map[&'a'], vec![2]);
+}
+
+#[test]
+fn spread_entry_closure_should_be_called_for_each_key() {
+    let words = vec!["a".to_string(), "bcd".to_string()];
+    let map = hash_map! { ..words.clone(): |word| word.len() };
+    assert_eq!(map["bcd"], 3);
+
+    let map = btree_map! { ..words.iter(): |word| word.to_uppercase() };
+    assert_eq!(map[&"a".to_string()], "A");
+
+    let pairs: Vec<_> = map_iter! { ..1..=2: |n| n * 10 }.collect();
+    assert_eq!(pairs, vec![(1, 10), (2, 20)]);
+}
+
+#[test]
+fn parenthesized_closure_should_be_the_value() {
+    let map = btree_map! { ..[1, 2]: (|n: u32| n + 1) };
+    assert_eq!(map[&2](5), 6);
+}
+
+#[test]
+fn spread_entry_keys_can_call_functions_named_like_helpers() {
+    fn per_key(n: u8) -> std::ops::RangeInclusive<u8> {
+        1..=n
+    }
+    let map = btree_map! { ..per_key(2): |k| *k + 1 };
+    assert_eq!(map, btree_map! { 1: 2, 2: 3 });
+}
+
+#[test]
+fn keyed_spread_values_can_call_functions_named_like_helpers() {
+    fn keyed(n: u8) -> std::ops::RangeInclusive<u8> {
+        1..=n
+    }
+    let map = btree_map! { ..keyed(2) => |v| *v * 10 };
+    assert_eq!(map, btree_map! { 10: 1, 20: 2 });
+}
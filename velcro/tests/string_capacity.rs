@@ -0,0 +1,20 @@
+//! Tests that `string!` allocates room for the lengths of its items and separators.
+use std::borrow::Cow;
+use velcro::string;
+
+#[test]
+fn capacity_should_include_the_lengths_of_single_values() {
+    let name = String::from("velcro");
+    let dir: Cow<str> = Cow::Borrowed("src");
+    let string = string!["crates/", name, '/', dir, 'é'];
+    assert_eq!(string, "crates/velcro/srcé");
+    assert_eq!(string.capacity(), string.len());
+}
+
+#[test]
+fn capacity_should_include_separators() {
+    let names = vec!["a", "b", "c"];
+    let string = string![@sep(", "); "[", ..names, "]"];
+    assert_eq!(string, "[a, b, c]");
+    assert_eq!(string.capacity(), string.len());
+}
@@ -0,0 +1,70 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::num::ParseIntError;
+use velcro::{hash_map, try_hash_map, try_vec};
+
+#[derive(Debug, PartialEq)]
+struct Error(String);
+
+impl From<ParseIntError> for Error {
+    fn from(error: ParseIntError) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[test]
+fn first_error_is_returned_before_spread_is_evaluated() {
+    let consumed = Cell::new(0);
+    let bad: Result<u32, &str> = Err("first");
+    let it = (0..3).map(|_| {
+        consumed.set(consumed.get() + 1);
+        Err::<u32, _>("spread")
+    });
+    let v: Result<Vec<u32>, &str> = try_vec![bad?, ..it?];
+    assert_eq!(v, Err("first"));
+    assert_eq!(consumed.get(), 0);
+}
+
+#[test]
+fn error_from_spread_stops_at_first_error() {
+    let v: Result<Vec<u32>, &str> = try_vec![1, ..vec![Ok(2), Err("a"), Err("b")]?, 3];
+    assert_eq!(v, Err("a"));
+}
+
+#[test]
+fn errors_are_converted_with_from() {
+    let v: Result<Vec<u32>, Error> = try_vec!["1".parse::<u32>()?, Ok::<_, Error>(2)?];
+    assert_eq!(v, Ok(vec![1, 2]));
+    let v: Result<Vec<u32>, Error> = try_vec!["x".parse::<u32>()?, Ok::<_, Error>(2)?];
+    assert!(v.is_err());
+}
+
+#[test]
+fn try_operator_unwraps_options() {
+    let (some, none): (Option<u32>, Option<u32>) = (Some(1), None);
+    let v: Option<Vec<u32>> = try_vec![some?, ..vec![Some(2), Some(3)]?];
+    assert_eq!(v, Some(vec![1, 2, 3]));
+    let v: Option<Vec<u32>> = try_vec![some?, none?];
+    assert_eq!(v, None);
+    let v: Option<Vec<u32>> = try_vec![..vec![Some(2), None]?];
+    assert_eq!(v, None);
+
+    let m: Option<HashMap<&str, u32>> = try_hash_map! { "a": some?, "b": 2 };
+    assert_eq!(m, Some(hash_map! { "a": 1, "b": 2 }));
+    let m: Option<HashMap<&str, u32>> = try_hash_map! { "a": some?, "b": none? };
+    assert_eq!(m, None);
+}
+
+#[test]
+fn try_operator_in_macro_arguments_does_not_return() {
+    fn format_all(bad: Result<u32, &'static str>) -> Result<Result<Vec<String>, &'static str>, ()> {
+        let values: Result<Vec<String>, &str> = try_vec![format!("{}", bad?), "b".into()];
+        Ok(values)
+    }
+    assert_eq!(format_all(Err("bad")), Ok(Err("bad")));
+    assert_eq!(format_all(Ok(1)), Ok(Ok(vec!["1".into(), "b".into()])));
+
+    let nested: Result<Vec<Vec<u32>>, &str> =
+        try_vec![velcro::vec![Ok(1)?, ..[2]], std::vec![Err("x")?; 2]];
+    assert_eq!(nested, Err("x"));
+}